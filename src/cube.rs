@@ -25,7 +25,7 @@ impl CubeArgs {
         if self.num_iterations == 0 {
             return Err(SimulationError::InvalidIterationCount(self.num_iterations));
         }
-        if self.dim <= 0 {
+        if self.dim == 0 {
             return Err(SimulationError::InvalidTarget(self.dim as i64));
         }
         if self.start >= self.dim {
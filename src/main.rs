@@ -2,7 +2,12 @@ use crate::cube::{cube_sim, CubeArgs};
 use crate::octo::{octo_sim, OctoArgs};
 use crate::shuffle::{shuffle_instance, shuffle_sim, ShuffleArgs};
 use crate::simplex::{simplex_sim, SimplexArgs};
-use crate::snl::{snakes_n_ladders, snl_simulation, SnlArgs};
+use crate::snl::{
+    list_boards, snakes_n_ladders, snl_distribution, snl_exact, snl_generate, snl_hot_squares, snl_mdp,
+    snl_race, snl_render, snl_replay, snl_sensitivity, snl_shortest, snl_simulation, snl_spectral,
+    DistributionArgs, ExactArgs, GenerateArgs, HotSquaresArgs, ListBoardsArgs, MdpArgs, RaceArgs,
+    RenderArgs, ReplayArgs, SensitivityArgs, ShortestArgs, SnlArgs, SpectralArgs,
+};
 use clap::{Parser, Subcommand}; // Added the necessary imports
 
 mod cube;
@@ -35,6 +40,41 @@ enum Commands {
     SnakesLadders(SnlArgs),
     #[command(name = "snl-simulation")]
     SnlSimulation(SnlArgs),
+    /// Compute exact expected game lengths from the Markov chain
+    #[command(name = "snl-exact")]
+    SnlExact(ExactArgs),
+    /// Compute the exact game length distribution from the Markov chain
+    #[command(name = "snl-distribution")]
+    SnlDistribution(DistributionArgs),
+    /// Race several players on one board and report seat win probabilities
+    #[command(name = "snl-race")]
+    SnlRace(RaceArgs),
+    /// Search for a random board with a target expected game length
+    #[command(name = "snl-generate")]
+    SnlGenerate(GenerateArgs),
+    /// Rank snakes and ladders by their effect on the expected game length
+    #[command(name = "snl-sensitivity")]
+    SnlSensitivity(SensitivityArgs),
+    /// Report expected visits per square and uses of each snake and ladder
+    #[command(name = "snl-hot-squares")]
+    SnlHotSquares(HotSquaresArgs),
+    /// Step through a trace file and re-check every game against the board
+    #[command(name = "replay", alias = "snl-replay")]
+    SnlReplay(ReplayArgs),
+    /// List the built-in board presets with their exact expected lengths
+    #[command(name = "list-boards")]
+    ListBoards(ListBoardsArgs),
+    /// Find the optimal strategy when players choose how to move, by value iteration
+    #[command(name = "snl-mdp")]
+    SnlMdp(MdpArgs),
+    /// Exact shortest game, its roll sequences and its probability from every square
+    #[command(name = "snl-shortest")]
+    SnlShortest(ShortestArgs),
+    /// Draw a board as text and SVG, optionally as a heatmap
+    Render(RenderArgs),
+    /// Tail decay rate of the game length from the eigenvalues of the transition matrix
+    #[command(name = "snl-spectral")]
+    SnlSpectral(SpectralArgs),
 }
 
 fn main() {
@@ -52,5 +92,29 @@ fn main() {
         Commands::SnlSimulation(args) => {
             snl_simulation(args).expect("Snakes and Ladders for All Starting Points")
         }
+        Commands::SnlExact(args) => snl_exact(args).expect("Exact Snakes and Ladders."),
+        Commands::SnlDistribution(args) => {
+            snl_distribution(args).expect("Snakes and Ladders Length Distribution.")
+        }
+        Commands::SnlRace(args) => snl_race(args).expect("Snakes and Ladders Race."),
+        Commands::SnlGenerate(args) => {
+            snl_generate(args).expect("Snakes and Ladders Board Generator.")
+        }
+        Commands::SnlSensitivity(args) => {
+            snl_sensitivity(args).expect("Snakes and Ladders Sensitivity.")
+        }
+        Commands::SnlHotSquares(args) => {
+            snl_hot_squares(args).expect("Snakes and Ladders Hot Squares.")
+        }
+        Commands::SnlReplay(args) => snl_replay(args).expect("Snakes and Ladders Replay."),
+        Commands::ListBoards(args) => list_boards(args).expect("Snakes and Ladders Presets."),
+        Commands::SnlMdp(args) => snl_mdp(args).expect("Snakes and Ladders Optimal Strategy."),
+        Commands::SnlShortest(args) => {
+            snl_shortest(args).expect("Snakes and Ladders Shortest Games.")
+        }
+        Commands::Render(args) => snl_render(args).expect("Snakes and Ladders Rendering."),
+        Commands::SnlSpectral(args) => {
+            snl_spectral(args).expect("Snakes and Ladders Spectral Analysis.")
+        }
     }
 }
@@ -30,7 +30,7 @@ pub struct ShuffleArgs {
 impl ShuffleArgs {
     /// Validate command line arguments
     fn validate(&self) -> Result<(), ShuffleError> {
-        if self.n <= 0 {
+        if self.n == 0 {
             return Err(ShuffleError::InvalidInputError(self.n));
         }
         Ok(())
@@ -20,7 +20,7 @@ impl SimplexArgs {
         if self.num_iterations == 0 {
             return Err(SimulationError::InvalidIterationCount(self.num_iterations));
         }
-        if self.dim <= 0 {
+        if self.dim == 0 {
             return Err(SimulationError::InvalidTarget(self.dim as i64));
         }
         Ok(())
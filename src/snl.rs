@@ -1,22 +1,71 @@
-use clap::{value_parser, Args};
+use clap::{value_parser, Args, ValueEnum};
 use rand::prelude::*;
 use rayon::prelude::*;
 use std::{
-    cmp::min,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs::File,
-    io::{self, Write},
-    sync::Arc,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
     time::Instant,
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use crate::stats::{SimulationError, SimulationStats};
 use crate::util::print_hms;
 
+use self::alias::AliasTable;
+use self::board_file::BoardFile;
+use self::dice::Dice;
+use self::grid::Grid;
+use self::movement::{MoveSource, MovementArgs};
+use self::presets::Preset;
+use self::sparse::SparseMatrix;
+use self::trace::TraceRoll;
+
+pub use self::distribution::{snl_distribution, DistributionArgs};
+pub use self::exact::{snl_exact, ExactArgs};
+pub use self::generate::{snl_generate, GenerateArgs};
+pub use self::hot_squares::{snl_hot_squares, HotSquaresArgs};
+pub use self::mdp::{snl_mdp, MdpArgs};
+pub use self::presets::{list_boards, ListBoardsArgs};
+pub use self::race::{snl_race, RaceArgs};
+pub use self::render::{snl_render, RenderArgs};
+pub use self::sensitivity::{snl_sensitivity, SensitivityArgs};
+pub use self::shortest::{snl_shortest, ShortestArgs};
+pub use self::spectral::{snl_spectral, SpectralArgs};
+pub use self::trace::{snl_replay, ReplayArgs};
+
+mod alias;
+mod board_file;
+mod dice;
+mod distribution;
+mod exact;
+mod generate;
+mod grid;
+mod hot_squares;
+mod mdp;
+mod movement;
+mod presets;
+mod race;
+mod render;
+mod sensitivity;
+mod shortest;
+mod sparse;
+mod spectral;
+mod trace;
+mod validation;
+
 /// Game constants
-const BOARD_SIZE: usize = 100;
+const STANDARD_GRID: Grid = Grid {
+    rows: 10,
+    columns: 10,
+};
 const DICE_SIDES: usize = 6;
+/// Highest rolls in a row that end a turn under a roll-again rule
+const ROLL_AGAIN_LIMIT: usize = 3;
 const OUTPUT_FILENAME: &str = "snl_results.csv";
+/// Rounding allowance when checking that a probability is one
+const CERTAINTY_TOLERANCE: f64 = 1e-12;
 
 /// Custom error type for Snakes and Ladders specific errors
 #[derive(Error, Debug)]
@@ -31,6 +80,201 @@ pub enum SnlError {
     IoError(#[from] io::Error),
     #[error("Simulation error: {0}")]
     SimError(#[from] SimulationError),
+    #[error("Unsupported board file format: {0} (expected .toml or .json)")]
+    BoardFormat(String),
+    #[error("Failed to parse board file {path} at line {line}, column {column} (field `{field}`): {message}")]
+    BoardParse {
+        path: String,
+        line: usize,
+        column: usize,
+        field: String,
+        message: String,
+    },
+    #[error("Invalid board file {path} (field `{field}`): {reason}")]
+    InvalidBoard {
+        path: String,
+        field: String,
+        reason: String,
+    },
+    #[error("Failed to write board file {0}: {1}")]
+    BoardWrite(String, String),
+    #[error("Invalid generator settings: {0}")]
+    InvalidGenerator(String),
+    #[error("Failed to parse trace line {line}: {message}")]
+    TraceParse { line: usize, message: String },
+    #[error("Trace line {line} does not match the board at roll {roll}: {reason}")]
+    ReplayMismatch {
+        line: usize,
+        roll: usize,
+        reason: String,
+    },
+    #[error("Snake or ladder {from}->{to} leaves the board of {size} squares")]
+    TransitionOffBoard { from: usize, to: usize, size: usize },
+    #[error("Snake or ladder starts on the final square {0}")]
+    TransitionFromFinal(usize),
+    #[error("Snake or ladder 0->{0} starts on the off-board start square 0")]
+    TransitionFromStart(usize),
+    #[error("More than one snake or ladder starts on square {0}")]
+    DuplicateStart(usize),
+    #[error("Snake or ladder {from}->{to} ends on the start of {to}->{next}; single-hop play would leave a token resting there, so play this board with --chaining chain")]
+    ChainedTransition { from: usize, to: usize, next: usize },
+    #[error("Snakes and ladders form a cycle through squares {0:?}")]
+    TransitionCycle(Vec<usize>),
+    #[error("The final square cannot be reached from square {0}")]
+    UnreachableFinish(usize),
+    #[error("Unknown preset {0}; run list-boards to see the available presets")]
+    UnknownPreset(String),
+    #[error("Unsupported rule: {0}")]
+    UnsupportedRule(String),
+    #[error("Invalid movement source: {0}")]
+    InvalidMovement(String),
+    #[error("Invalid threshold: {0} must be between 0 and 1")]
+    InvalidThreshold(f64),
+    #[error("Iteration did not converge within {0} sweeps")]
+    NoConvergence(usize),
+    #[error("Invalid number of players: {0} must be greater than zero")]
+    InvalidPlayers(usize),
+    #[error("Invalid dice: {0}")]
+    InvalidDice(String),
+    #[error("Singular matrix: the finish cannot be reached from every square")]
+    SingularMatrix,
+}
+
+/// How a roll that would pass the final square is resolved
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Overshoot {
+    /// Stop on the final square, so overshooting still wins
+    #[default]
+    Cap,
+    /// Stay put; the final square must be reached exactly
+    Stay,
+    /// Bounce back from the final square by the excess
+    Bounce,
+}
+
+impl Overshoot {
+    /// Square reached when a roll from `position` targets `target` on a board of `size` squares
+    fn apply(self, position: usize, target: usize, size: usize) -> usize {
+        if target <= size {
+            return target;
+        }
+        match self {
+            Overshoot::Cap => size,
+            Overshoot::Stay => position,
+            Overshoot::Bounce => size.saturating_sub(target - size),
+        }
+    }
+}
+
+/// How a token moves when a snake or ladder ends on the start of another
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Chaining {
+    /// Follow one snake or ladder per landing; boards may not chain them
+    #[default]
+    Single,
+    /// Keep following snakes and ladders until the token stops
+    Chain,
+}
+
+/// Bonus rolls for rolling the highest possible total, e.g. a six on a single d6
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum RollAgain {
+    /// No bonus rolls
+    #[default]
+    Off,
+    /// Roll again; the third highest roll in a row forfeits the turn
+    Forfeit,
+    /// Roll again; the third highest roll in a row sends the token back to the start
+    Restart,
+}
+
+/// Command line arguments selecting the board to play
+#[derive(Args)]
+pub struct BoardArgs {
+    /// Board definition file (.toml or .json); defaults to the standard board
+    #[arg(long = "board", required = false)]
+    board: Option<PathBuf>,
+
+    /// Built-in board to play instead of a board file; see list-boards
+    #[arg(long = "preset", required = false, conflicts_with = "board")]
+    preset: Option<String>,
+
+    #[command(flatten)]
+    rules: RuleArgs,
+}
+
+/// Command line arguments selecting the movement rules
+#[derive(Args)]
+pub struct RuleArgs {
+    /// Rule for rolls that would pass the final square [default: cap]; overrides the board
+    /// file
+    #[arg(long = "overshoot", required = false, value_enum)]
+    overshoot: Option<Overshoot>,
+
+    /// Whether snakes and ladders chain into each other; overrides the board file
+    #[arg(long = "chaining", required = false, value_enum)]
+    chaining: Option<Chaining>,
+
+    /// Bonus roll rule for rolling the highest possible total [default: off]; overrides the
+    /// board file
+    #[arg(long = "roll-again", required = false, value_enum)]
+    roll_again: Option<RollAgain>,
+
+    /// Number of sides on each die; overrides the board file
+    #[arg(long = "dice-sides", required = false, value_parser = value_parser!(usize))]
+    dice_sides: Option<usize>,
+
+    /// Number of dice rolled and summed each turn; overrides the board file
+    #[arg(long = "dice-count", required = false, value_parser = value_parser!(usize))]
+    dice_count: Option<usize>,
+
+    /// Comma separated relative weight of each face, for loaded dice; overrides the board file
+    #[arg(long = "dice-weights", required = false, value_delimiter = ',')]
+    dice_weights: Option<Vec<f64>>,
+}
+
+impl RuleArgs {
+    /// Rules for a board read from `file`, if any, with command line overrides applied
+    fn rules(&self, file: Option<&BoardFile>) -> Rules {
+        Rules {
+            overshoot: self.overshoot.or(file.and_then(|f| f.overshoot)).unwrap_or_default(),
+            chaining: self.chaining.or(file.and_then(|f| f.chaining)).unwrap_or_default(),
+            roll_again: self.roll_again.or(file.and_then(|f| f.roll_again)).unwrap_or_default(),
+            dice: self.dice(file.and_then(|f| f.dice.clone())),
+        }
+    }
+
+    /// Whether any dice option was given on the command line
+    fn overrides_dice(&self) -> bool {
+        self.dice_sides.is_some() || self.dice_count.is_some() || self.dice_weights.is_some()
+    }
+
+    /// Dice from the board file, if any, with command line overrides applied
+    fn dice(&self, file_dice: Option<Dice>) -> Dice {
+        let mut dice = file_dice.unwrap_or_default();
+        if let Some(sides) = self.dice_sides {
+            dice.sides = sides;
+        }
+        if let Some(count) = self.dice_count {
+            dice.count = count;
+        }
+        if let Some(weights) = &self.dice_weights {
+            dice.weights = Some(weights.clone());
+        }
+        dice
+    }
+}
+
+/// Rules that shape how a token moves around the board
+#[derive(Clone, Debug, Default)]
+struct Rules {
+    overshoot: Overshoot,
+    chaining: Chaining,
+    roll_again: RollAgain,
+    dice: Dice,
 }
 
 /// Command line arguments for the Snakes and Ladders simulation
@@ -43,31 +287,111 @@ pub struct SnlArgs {
     /// Starting position on the board
     #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
     start: usize,
+
+    /// Write every roll of every game to this file as JSON Lines, one game per line
+    #[arg(long = "trace", required = false)]
+    trace: Option<PathBuf>,
+
+    #[command(flatten)]
+    movement: MovementArgs,
+
+    #[command(flatten)]
+    board: BoardArgs,
 }
 
 impl SnlArgs {
-    /// Validate command line arguments
-    fn validate(&self) -> Result<(), SnlError> {
-        if self.start >= BOARD_SIZE {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
             return Err(SnlError::InvalidStart(self.start));
         }
         Ok(())
     }
 }
 
+/// CSV column prefix and printed label of each per-game metric besides the length
+const GAME_METRICS: [(&str, &str); 5] = [
+    ("snakes", "Snakes hit"),
+    ("ladders", "Ladders climbed"),
+    ("snake_distance", "Squares fallen down snakes"),
+    ("ladder_distance", "Squares climbed up ladders"),
+    ("worst_fall", "Worst single fall"),
+];
+
+/// What happened in a single simulated game
+#[derive(Debug, Default)]
+struct GameMetrics {
+    moves: u64,
+    snakes: u64,
+    ladders: u64,
+    snake_distance: u64,
+    ladder_distance: u64,
+    /// Longest single snake taken, or zero if none was
+    worst_fall: u64,
+}
+
+impl GameMetrics {
+    /// Count each snake and ladder taken on a roll, following any chain hop by hop
+    fn record(&mut self, roll: &TraceRoll, board: &GameBoard) {
+        if roll.penalty || !board.transitions.contains_key(&roll.landed) {
+            return;
+        }
+        for from in std::iter::once(roll.landed).chain(board.chained_starts(roll.landed)) {
+            let to = board.transitions[&from];
+            if to < from {
+                let fall = (from - to) as u64;
+                self.snakes += 1;
+                self.snake_distance += fall;
+                self.worst_fall = self.worst_fall.max(fall);
+            } else {
+                self.ladders += 1;
+                self.ladder_distance += (to - from) as u64;
+            }
+        }
+    }
+
+    /// Values of the metrics in `GAME_METRICS` order
+    fn values(&self) -> [u64; 5] {
+        [
+            self.snakes,
+            self.ladders,
+            self.snake_distance,
+            self.ladder_distance,
+            self.worst_fall,
+        ]
+    }
+}
+
 /// Statistics for a specific starting position
 #[derive(Debug)]
 struct SnlStatistics {
     start: usize,
     statistics: SimulationStats,
+    /// Statistics of each of `GAME_METRICS`, or `None` where every game had the same value
+    metrics: Vec<Option<SimulationStats>>,
 }
 
 impl SnlStatistics {
     /// Create new statistics from simulation results
-    fn new(start: usize, moves: &[u64]) -> Result<Self, SnlError> {
+    fn new(start: usize, games: &[GameMetrics]) -> Result<Self, SnlError> {
+        let moves: Vec<u64> = games.iter().map(|game| game.moves).collect();
+        let metrics = (0..GAME_METRICS.len())
+            .map(|i| {
+                let values: Vec<u64> = games.iter().map(|game| game.values()[i]).collect();
+                SimulationStats::from_values(&values).ok()
+            })
+            .collect();
+        // Games of one fixed length have no spread, e.g. from a square every roll finishes
+        let statistics = match moves.first() {
+            Some(&first) if moves.iter().all(|&m| m == first) => {
+                SimulationStats::certain(first, Some(first))
+            }
+            _ => SimulationStats::from_values(&moves)?,
+        };
         Ok(Self {
             start,
-            statistics: SimulationStats::from_values(moves)?,
+            statistics,
+            metrics,
         })
     }
 
@@ -75,42 +399,120 @@ impl SnlStatistics {
     fn print(&self) {
         println!("Start: {}", self.start);
         self.statistics.print();
+        for ((_, label), metric) in GAME_METRICS.iter().zip(self.metrics.iter()) {
+            match metric {
+                Some(statistics) => println!("{}: {}", label, statistics.summary()),
+                None => println!("{}: the same in every game", label),
+            }
+        }
     }
 
     /// Get CSV header string
     fn header(&self) -> String {
-        format!("start,{}", self.statistics.header())
+        let mut header = format!("start,{}", self.statistics.header());
+        for (name, _) in GAME_METRICS.iter().take(self.metrics.len()) {
+            for field in self.statistics.header().split(',') {
+                header += &format!(",{}_{}", name, field.replace("_moves", ""));
+            }
+        }
+        header
     }
 
     /// Write statistics to CSV file
     fn write_to_csv(&self, file: &mut File) -> io::Result<()> {
-        writeln!(file, "{},{}", self.start, self.statistics.write())
+        let fields = self.statistics.header().split(',').count();
+        let mut row = format!("{},{}", self.start, self.statistics.write());
+        for metric in &self.metrics {
+            match metric {
+                Some(statistics) => row += &format!(",{}", statistics.write()),
+                None => row += &",".repeat(fields),
+            }
+        }
+        writeln!(file, "{}", row)
     }
 }
 
+/// Where one turn from a given square ends, and where its rolls land along the way
+///
+/// Both lists hold `(square, value)` pairs in increasing square order, leaving out
+/// squares the turn cannot reach.
+#[derive(Debug)]
+struct Turn {
+    /// Probability of the turn ending on each square
+    outcomes: Vec<(usize, f64)>,
+    /// Expected number of rolls landing on each square during the turn
+    landings: Vec<(usize, f64)>,
+}
+
 /// Represents the Snakes and Ladders game board
 #[derive(Debug)]
 struct GameBoard {
+    name: String,
+    grid: Grid,
+    /// Number of squares in the grid, which is also the final square
+    size: usize,
     transitions: HashMap<usize, usize>,
-    transition_matrix: Vec<Vec<f64>>,
-    possible_positions: Arc<Vec<usize>>,
+    rules: Rules,
+    /// Probability of a turn from each square ending on each square
+    transition_matrix: SparseMatrix,
+    /// Where a turn from each square ends, drawn in constant time; `None` on the start of
+    /// a snake or ladder, where no turn begins
+    turn_tables: Vec<Option<AliasTable>>,
+    /// Rolls of the board's dice, the default source of moves in simulated games
+    dice_source: MoveSource,
 }
 
 impl GameBoard {
-    /// Create a new game board with standard snakes and ladders
-    fn new() -> Result<Self, SnlError> {
-        let transitions = Self::create_snakes_and_ladders_map();
-        let transition_matrix = Self::create_transition_matrix(&transitions)?;
-        let possible_positions = Arc::new((0..=BOARD_SIZE).collect());
+    /// Create a new game board on the given grid with the given snakes and ladders
+    fn new(
+        name: String,
+        grid: Grid,
+        transitions: HashMap<usize, usize>,
+        rules: Rules,
+    ) -> Result<Self, SnlError> {
+        let size = grid.size();
+        rules.dice.validate()?;
+        Self::validate_transitions(size, &transitions, rules.chaining)?;
+        let dice_source = MoveSource::weighted(&rules.dice.distribution())?;
 
-        Ok(Self {
+        let mut board = Self {
+            name,
+            grid,
+            size,
             transitions,
-            transition_matrix,
-            possible_positions,
-        })
+            rules,
+            transition_matrix: SparseMatrix::default(),
+            turn_tables: Vec::new(),
+            dice_source,
+        };
+        board.transition_matrix = board.create_transition_matrix()?;
+        board.turn_tables = (0..=size)
+            .map(|square| AliasTable::new(board.transition_matrix.row(square)))
+            .collect();
+        board.validate_reachable()?;
+        Ok(board)
+    }
+
+    /// Load the board file or preset given, or else create the standard board
+    fn from_args(args: &BoardArgs) -> Result<Self, SnlError> {
+        match &args.board {
+            Some(path) => {
+                let file = BoardFile::load(path)?;
+                let name = file.name.clone().unwrap_or_else(|| path.display().to_string());
+                let rules = args.rules.rules(Some(&file));
+                Self::new(name, file.grid(path)?, file.transitions()?, rules)
+            }
+            None => {
+                let preset = match &args.preset {
+                    Some(name) => Preset::find(name)?,
+                    None => Preset::default_preset(),
+                };
+                preset.board(args.rules.rules(None))
+            }
+        }
     }
 
-    /// Create the standard snakes and ladders map
+    /// Create the standard snakes and ladders map, as in the Milton Bradley Chutes and Ladders
     fn create_snakes_and_ladders_map() -> HashMap<usize, usize> {
         HashMap::from([
             (1, 38), (4, 14), (9, 31), (16, 6), (21, 42),
@@ -120,72 +522,237 @@ impl GameBoard {
         ])
     }
 
-    /// Create probability transition matrix for the game
-    fn create_transition_matrix(
-        transitions: &HashMap<usize, usize>,
-    ) -> Result<Vec<Vec<f64>>, SnlError> {
-        let mut matrix = vec![vec![0.0; BOARD_SIZE + 1]; BOARD_SIZE + 1];
+    /// Create probability transition matrix for the game, with one step per turn
+    ///
+    /// Only the squares a turn can end on are stored, so the matrix stays small on
+    /// large boards. Rows for the starts of snakes and ladders are empty.
+    fn create_transition_matrix(&self) -> Result<SparseMatrix, SnlError> {
+        let rolls = self.rules.dice.distribution();
+        let rows = (0..=self.size).map(|i| {
+            if i == self.size {
+                // The final square is absorbing whatever the overshoot rule
+                vec![(self.size, 1.0)]
+            } else if self.transitions.contains_key(&i) {
+                Vec::new()
+            } else {
+                self.turn_outcomes(i, &rolls)
+            }
+        });
+        Ok(SparseMatrix::from_rows(rows))
+    }
+
+    /// Probability of a turn starting on `square` ending on each square of the board
+    fn turn_outcomes(&self, square: usize, rolls: &[f64]) -> Vec<(usize, f64)> {
+        self.resolve_turn(square, rolls, None).outcomes
+    }
+
+    /// Resolve a turn starting on `square`, recording where it ends and where its rolls land
+    ///
+    /// Under a roll-again rule the number of highest rolls made so far this turn is part
+    /// of the state while the turn is resolved. It resets at the end of every turn, so the
+    /// chain over squares stays Markov with one step per turn rather than per roll.
+    ///
+    /// A roll lands on the square it moves the token to, before any snake or ladder, and
+    /// under chaining on the start of each further one it follows. If `stop_at` is given,
+    /// probability that lands there is recorded but leaves the turn, so the outcomes only
+    /// cover turns that never land on it.
+    fn resolve_turn(&self, square: usize, rolls: &[f64], stop_at: Option<usize>) -> Turn {
+        let mut outcomes = BTreeMap::new();
+        let mut landings = BTreeMap::new();
+        let add = |map: &mut BTreeMap<usize, f64>, square: usize, p: f64| {
+            *map.entry(square).or_insert(0.0) += p;
+        };
+        let highest = rolls.iter().rposition(|&p| p > 0.0).unwrap_or(0);
+        // Probability of still being mid-turn on each square after `streak` highest rolls
+        let mut rolling = BTreeMap::from([(square, 1.0)]);
 
-        for (i, row) in matrix.iter_mut().enumerate().take(BOARD_SIZE + 1) {
-            if !transitions.contains_key(&i) {
-                for j in (i + 1)..=(i + DICE_SIDES) {
-                    let destination = *min(transitions.get(&j).unwrap_or(&j), &BOARD_SIZE);
-                    row[destination] += 1.0 / DICE_SIDES as f64;
+        for streak in 0..ROLL_AGAIN_LIMIT {
+            let mut next = BTreeMap::new();
+            for (&position, &p) in &rolling {
+                for (roll, &q) in rolls.iter().enumerate().filter(|(_, &q)| q > 0.0) {
+                    let bonus = roll == highest && self.rules.roll_again != RollAgain::Off;
+                    if bonus && streak + 1 == ROLL_AGAIN_LIMIT {
+                        let penalty = match self.rules.roll_again {
+                            RollAgain::Restart => 0,
+                            _ => square,
+                        };
+                        add(&mut outcomes, penalty, p * q);
+                        continue;
+                    }
+
+                    let landed = self.rules.overshoot.apply(position, position + roll, self.size);
+                    if landed != position {
+                        let chained = self.chained_starts(landed);
+                        for &arrived in std::iter::once(&landed).chain(chained.iter()) {
+                            add(&mut landings, arrived, p * q);
+                        }
+                        if stop_at.is_some_and(|stop| stop == landed || chained.contains(&stop)) {
+                            continue;
+                        }
+                    }
+                    let destination = self.follow(landed);
+                    if bonus && destination != self.size {
+                        add(&mut next, destination, p * q);
+                    } else {
+                        add(&mut outcomes, destination, p * q);
+                    }
                 }
             }
+            rolling = next;
+        }
+        Turn {
+            outcomes: outcomes.into_iter().collect(),
+            landings: landings.into_iter().collect(),
         }
-        Ok(matrix)
     }
 
-    /// Simulate multiple games with given parameters
+    /// Square a token rests on after arriving on `square`, following any snake or ladder
+    /// there, and under chaining any that start where it ends
+    fn follow(&self, square: usize) -> usize {
+        let mut square = *self.transitions.get(&square).unwrap_or(&square);
+        if self.rules.chaining == Chaining::Chain {
+            // Validation rejects cycles, so this always stops
+            while let Some(&next) = self.transitions.get(&square) {
+                square = next;
+            }
+        }
+        square
+    }
+
+    /// Starts of the snakes and ladders followed after the first from `square`, which is
+    /// always empty without chaining
+    fn chained_starts(&self, square: usize) -> Vec<usize> {
+        let mut starts = Vec::new();
+        if self.rules.chaining == Chaining::Chain {
+            let mut square = *self.transitions.get(&square).unwrap_or(&square);
+            while let Some(&next) = self.transitions.get(&square) {
+                starts.push(square);
+                square = next;
+            }
+        }
+        starts
+    }
+
+    /// Source of moves rolling the board's dice
+    fn dice_source(&self) -> MoveSource {
+        self.dice_source.clone()
+    }
+
+    /// Simulate multiple games with given parameters, each with its own copy of `source`
     fn simulate_games(
         &self,
         num_iterations: u64,
         start_position: usize,
-    ) -> Result<Vec<u64>, SnlError> {
-        if start_position >= BOARD_SIZE {
+        source: &MoveSource,
+    ) -> Result<Vec<GameMetrics>, SnlError> {
+        if start_position >= self.size {
             return Err(SnlError::InvalidPosition(start_position));
         }
 
-        let moves = (0..num_iterations)
+        let games = (0..num_iterations)
             .into_par_iter()
-            .map(|_| self.simulate_single_game(start_position))
+            .map(|_| self.simulate_single_game(start_position, source.clone()))
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(moves)
+        Ok(games)
+    }
+
+    /// Simulate a single game from start to finish, roll by roll
+    fn simulate_single_game(
+        &self,
+        start_position: usize,
+        mut source: MoveSource,
+    ) -> Result<GameMetrics, SnlError> {
+        let mut metrics = GameMetrics::default();
+        metrics.moves = self.play_game(&mut thread_rng(), &mut source, start_position, |roll| {
+            metrics.record(&roll, self)
+        });
+        Ok(metrics)
     }
 
-    /// Simulate a single game from start to finish
-    fn simulate_single_game(&self, start_position: usize) -> Result<u64, SnlError> {
-        let mut rng = thread_rng();
-        let mut moves = 0;
+    /// Play one game roll by roll from `start_position`, drawing each move from `source`
+    /// and passing every roll to `on_roll`, and return the number of turns taken
+    ///
+    /// Rolls follow the same rules as the transition matrix, so with the board's dice the
+    /// number of turns has the same distribution as repeated calls to `take_turn`.
+    fn play_game<R: Rng, F: FnMut(TraceRoll)>(
+        &self,
+        rng: &mut R,
+        source: &mut MoveSource,
+        start_position: usize,
+        mut on_roll: F,
+    ) -> u64 {
+        let mut turns = 0;
         let mut position = start_position;
+        let highest = source.highest();
 
-        while position != BOARD_SIZE {
-            moves += 1;
-            position = *self.possible_positions
-                .choose_weighted(&mut rng, |&pos| self.transition_matrix[position][pos])
-                .map_err(|_| SnlError::TransitionMatrixError)?;
+        while position != self.size {
+            turns += 1;
+            let turn_start = position;
+            for streak in 0..ROLL_AGAIN_LIMIT {
+                let roll = source.next_move(rng);
+                let from = position;
+                let bonus = roll == highest && self.rules.roll_again != RollAgain::Off;
+                let penalty = bonus && streak + 1 == ROLL_AGAIN_LIMIT;
+                let landed = if penalty {
+                    from
+                } else {
+                    self.rules.overshoot.apply(from, from + roll, self.size)
+                };
+                position = match (penalty, self.rules.roll_again) {
+                    (true, RollAgain::Restart) => 0,
+                    (true, _) => turn_start,
+                    _ => self.follow(landed),
+                };
+                on_roll(TraceRoll {
+                    turn: turns,
+                    roll,
+                    from,
+                    landed,
+                    to: position,
+                    penalty,
+                });
+                if !bonus || penalty || position == self.size {
+                    break;
+                }
+            }
         }
+        turns
+    }
 
-        Ok(moves)
+    /// Play one turn from `position`, returning the square the token ends on
+    fn take_turn<R: Rng>(&self, rng: &mut R, position: usize) -> Result<usize, SnlError> {
+        self.turn_tables
+            .get(position)
+            .and_then(Option::as_ref)
+            .map(|table| table.sample(rng))
+            .ok_or(SnlError::TransitionMatrixError)
     }
 }
 
 /// Run full simulation and save results to CSV
 pub fn snl_simulation(args: SnlArgs) -> Result<(), SnlError> {
-    args.validate()?;
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    let source = args.movement.source(&board)?;
     let start_time = Instant::now();
     let mut file = File::create(OUTPUT_FILENAME)?;
-    let board = GameBoard::new()?;
+    let mut trace = args.trace.as_ref().map(File::create).transpose()?.map(BufWriter::new);
 
     // Run simulation for each valid starting position
-    for start in 0..99 {
+    let mut header_written = false;
+    for start in 0..board.size {
         if !board.transitions.contains_key(&start) {
-            let moves = board.simulate_games(args.num_iterations, start)?;
-            let stats = SnlStatistics::new(start, &moves)?;
-            if start == 0 {
+            let games = match trace.as_mut() {
+                Some(writer) => {
+                    board.simulate_traced_games(args.num_iterations, start, &source, writer)?
+                }
+                None => board.simulate_games(args.num_iterations, start, &source)?,
+            };
+            let stats = SnlStatistics::new(start, &games)?;
+            if !header_written {
                 writeln!(file, "{}", stats.header())?;
+                header_written = true;
             }
             stats.write_to_csv(&mut file)?;
         }
@@ -197,15 +764,23 @@ pub fn snl_simulation(args: SnlArgs) -> Result<(), SnlError> {
 
 /// Run simulation for a single starting position
 pub fn snakes_n_ladders(args: SnlArgs) -> Result<(), SnlError> {
-    args.validate()?;
-    let board = GameBoard::new()?;
-    let start_position = *board.transitions.get(&args.start).unwrap_or(&args.start);
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    let source = args.movement.source(&board)?;
+    println!("Board: {}", board.name);
+    let start_position = board.follow(args.start);
 
     let start_time = Instant::now();
-    let moves = board.simulate_games(args.num_iterations, start_position)?;
+    let games = match &args.trace {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            board.simulate_traced_games(args.num_iterations, start_position, &source, &mut writer)?
+        }
+        None => board.simulate_games(args.num_iterations, start_position, &source)?,
+    };
     print_hms(&start_time);
 
-    let stats = SnlStatistics::new(args.start, &moves)?;
+    let stats = SnlStatistics::new(args.start, &games)?;
     stats.print();
 
     Ok(())
@@ -0,0 +1,94 @@
+use rand::Rng;
+
+/// Walker's alias table, for drawing from a fixed discrete distribution in constant time
+///
+/// Only outcomes with positive weight are stored, so a sparse row of the transition
+/// matrix costs one column per square a turn can actually end on. Each draw picks a
+/// column uniformly, then either its own outcome or its alias.
+#[derive(Clone, Debug)]
+pub(super) struct AliasTable {
+    /// Outcome of each column
+    outcomes: Vec<usize>,
+    /// Probability of drawing the column's own outcome rather than its alias
+    keep: Vec<f64>,
+    /// Column whose outcome is drawn otherwise
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// Table drawing each outcome in proportion to its weight, or `None` if no weight
+    /// is positive
+    pub(super) fn new(weights: impl IntoIterator<Item = (usize, f64)>) -> Option<Self> {
+        let (outcomes, weights): (Vec<usize>, Vec<f64>) =
+            weights.into_iter().filter(|&(_, w)| w > 0.0).unzip();
+        let total: f64 = weights.iter().sum();
+        if outcomes.is_empty() || !total.is_finite() {
+            return None;
+        }
+
+        // Scale so the average column holds exactly 1, then top up each short column
+        // from a long one (Vose's method)
+        let n = outcomes.len();
+        let mut keep: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut short, mut long): (Vec<usize>, Vec<usize>) = (0..n).partition(|&i| keep[i] < 1.0);
+        while let (Some(&small), Some(&large)) = (short.last(), long.last()) {
+            short.pop();
+            alias[small] = large;
+            keep[large] -= 1.0 - keep[small];
+            if keep[large] < 1.0 {
+                long.pop();
+                short.push(large);
+            }
+        }
+        // Whatever is left is full up to rounding
+        for i in short.into_iter().chain(long) {
+            keep[i] = 1.0;
+        }
+
+        Some(AliasTable {
+            outcomes,
+            keep,
+            alias,
+        })
+    }
+
+    /// Draw an outcome
+    pub(super) fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        let column = rng.gen_range(0..self.outcomes.len());
+        if rng.gen::<f64>() < self.keep[column] {
+            self.outcomes[column]
+        } else {
+            self.outcomes[self.alias[column]]
+        }
+    }
+
+    /// Whether `outcome` has positive probability
+    pub(super) fn contains(&self, outcome: usize) -> bool {
+        self.outcomes.contains(&outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn draws_follow_the_weights() {
+        let weights = [(2, 1.0), (5, 0.0), (7, 3.0), (9, 6.0)];
+        let table = AliasTable::new(weights).unwrap();
+        assert!(!table.contains(5));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let draws = 1_000_000;
+        let mut counts = [0usize; 10];
+        for _ in 0..draws {
+            counts[table.sample(&mut rng)] += 1;
+        }
+        for (outcome, weight) in weights {
+            let frequency = counts[outcome] as f64 / draws as f64;
+            assert!((frequency - weight / 10.0).abs() < 0.005, "{}: {}", outcome, frequency);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+use super::{dice::Dice, grid::Grid, Chaining, Overshoot, RollAgain, SnlError};
+
+/// Board definition as read from a TOML or JSON file
+///
+/// Snakes and ladders are listed as `[start, end]` pairs, e.g. in TOML:
+///
+/// ```toml
+/// name = "Example"
+/// rows = 10
+/// columns = 10
+/// snakes = [[16, 6], [47, 26]]
+/// ladders = [[1, 38], [4, 14]]
+///
+/// [dice]
+/// sides = 6
+/// count = 1
+/// weights = [1, 1, 1, 1, 1, 2]
+/// ```
+///
+/// Instead of `rows` and `columns`, a board may give just its `size`, which is laid out
+/// on the most nearly square grid with that many squares.
+///
+/// The `dice` table is optional and defaults to a single fair six-sided die. Setting
+/// `chaining = "chain"` lets a snake or ladder end on the start of another, which the
+/// token then follows too; the default, `"single"`, follows one per landing. The
+/// `overshoot` (`"cap"`, `"stay"` or `"bounce"`) and `roll-again` (`"off"`, `"forfeit"`
+/// or `"restart"`) rules may be stored the same way. Command line options override all
+/// of these.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BoardFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rows: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) columns: Option<usize>,
+    #[serde(default)]
+    pub(crate) snakes: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub(crate) ladders: Vec<(usize, usize)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) chaining: Option<Chaining>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) overshoot: Option<Overshoot>,
+    #[serde(default, rename = "roll-again", skip_serializing_if = "Option::is_none")]
+    pub(crate) roll_again: Option<RollAgain>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) dice: Option<Dice>,
+}
+
+impl BoardFile {
+    /// Read and parse a board file, choosing the format from its extension
+    pub(crate) fn load(path: &Path) -> Result<Self, SnlError> {
+        let contents = fs::read_to_string(path)?;
+        let board = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::parse_toml(path, &contents)?,
+            Some("json") => Self::parse_json(path, &contents)?,
+            _ => return Err(SnlError::BoardFormat(path.display().to_string())),
+        };
+        board.check(path)?;
+        Ok(board)
+    }
+
+    fn parse_toml(path: &Path, contents: &str) -> Result<Self, SnlError> {
+        serde_path_to_error::deserialize(toml::Deserializer::new(contents)).map_err(|e| {
+            let field = e.path().to_string();
+            let inner = e.into_inner();
+            let (line, column) = inner
+                .span()
+                .map(|span| line_column(contents, span.start))
+                .unwrap_or((0, 0));
+            SnlError::BoardParse {
+                path: path.display().to_string(),
+                line,
+                column,
+                field,
+                message: inner.message().to_string(),
+            }
+        })
+    }
+
+    fn parse_json(path: &Path, contents: &str) -> Result<Self, SnlError> {
+        let mut deserializer = serde_json::Deserializer::from_str(contents);
+        serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+            let field = e.path().to_string();
+            let inner = e.into_inner();
+            SnlError::BoardParse {
+                path: path.display().to_string(),
+                line: inner.line(),
+                column: inner.column(),
+                field,
+                message: inner.to_string(),
+            }
+        })
+    }
+
+    /// Write the board to a file, choosing the format from its extension
+    pub(crate) fn save(&self, path: &Path) -> Result<(), SnlError> {
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::to_string(self)
+                .map_err(|e| SnlError::BoardWrite(path.display().to_string(), e.to_string()))?,
+            Some("json") => serde_json::to_string_pretty(self)
+                .map_err(|e| SnlError::BoardWrite(path.display().to_string(), e.to_string()))?,
+            _ => return Err(SnlError::BoardFormat(path.display().to_string())),
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Grid the board is laid out on, from `rows` and `columns` or else from `size`
+    pub(crate) fn grid(&self, path: &Path) -> Result<Grid, SnlError> {
+        let invalid = |field: &str, reason: &str| SnlError::InvalidBoard {
+            path: path.display().to_string(),
+            field: field.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let grid = match (self.rows, self.columns, self.size) {
+            (Some(rows), Some(columns), _) => Grid { rows, columns },
+            (Some(_), None, _) => return Err(invalid("columns", "rows given without columns")),
+            (None, Some(_), _) => return Err(invalid("rows", "columns given without rows")),
+            (None, None, Some(size)) => Grid::for_size(size),
+            (None, None, None) => return Err(invalid("size", "give a size or rows and columns")),
+        };
+        if grid.size() == 0 {
+            return Err(invalid("size", "board size must be positive"));
+        }
+        if self.size.is_some_and(|size| size != grid.size()) {
+            return Err(invalid("size", "size does not match rows times columns"));
+        }
+        Ok(grid)
+    }
+
+    /// Check that every snake goes down and every ladder goes up within the board
+    fn check(&self, path: &Path) -> Result<(), SnlError> {
+        let invalid = |field: String, reason: &str| SnlError::InvalidBoard {
+            path: path.display().to_string(),
+            field,
+            reason: reason.to_string(),
+        };
+
+        let size = self.grid(path)?.size();
+        for (i, &(start, end)) in self.snakes.iter().enumerate() {
+            if end >= start {
+                return Err(invalid(format!("snakes[{}]", i), "snake must end below its start"));
+            }
+            if start > size {
+                return Err(invalid(format!("snakes[{}]", i), "square is off the board"));
+            }
+        }
+        for (i, &(start, end)) in self.ladders.iter().enumerate() {
+            if end <= start {
+                return Err(invalid(format!("ladders[{}]", i), "ladder must end above its start"));
+            }
+            if start == 0 {
+                return Err(invalid(format!("ladders[{}]", i), "ladder must start on the board"));
+            }
+            if end > size {
+                return Err(invalid(format!("ladders[{}]", i), "square is off the board"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Combine snakes and ladders into a single transition map
+    pub(crate) fn transitions(&self) -> Result<HashMap<usize, usize>, SnlError> {
+        let mut transitions = HashMap::new();
+        for &(start, end) in self.snakes.iter().chain(self.ladders.iter()) {
+            if transitions.insert(start, end).is_some() {
+                return Err(SnlError::DuplicateStart(start));
+            }
+        }
+        Ok(transitions)
+    }
+}
+
+/// Convert a byte offset into a 1-based line and column
+fn line_column(contents: &str, offset: usize) -> (usize, usize) {
+    let prefix = &contents[..offset.min(contents.len())];
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.len() - prefix.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
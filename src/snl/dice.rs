@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use super::{SnlError, DICE_SIDES};
+
+/// Dice rolled each turn: `count` dice of `sides` faces, summed
+///
+/// Faces are fair unless `weights` gives a relative weight for each face in order.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Dice {
+    #[serde(default = "default_sides")]
+    pub(crate) sides: usize,
+    #[serde(default = "default_count")]
+    pub(crate) count: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) weights: Option<Vec<f64>>,
+}
+
+fn default_sides() -> usize {
+    DICE_SIDES
+}
+
+fn default_count() -> usize {
+    1
+}
+
+impl Default for Dice {
+    fn default() -> Self {
+        Self {
+            sides: default_sides(),
+            count: default_count(),
+            weights: None,
+        }
+    }
+}
+
+impl Dice {
+    /// Check that the dice can be rolled and the weights form a distribution
+    pub(crate) fn validate(&self) -> Result<(), SnlError> {
+        if self.sides == 0 {
+            return Err(SnlError::InvalidDice("dice must have at least one side".to_string()));
+        }
+        if self.count == 0 {
+            return Err(SnlError::InvalidDice("at least one die must be rolled".to_string()));
+        }
+        if let Some(weights) = &self.weights {
+            if weights.len() != self.sides {
+                return Err(SnlError::InvalidDice(format!(
+                    "{} weights given for {} sides",
+                    weights.len(),
+                    self.sides
+                )));
+            }
+            if weights.iter().any(|&w| !(w.is_finite() && w >= 0.0)) {
+                return Err(SnlError::InvalidDice("weights must be non-negative".to_string()));
+            }
+            if weights.iter().sum::<f64>() <= 0.0 {
+                return Err(SnlError::InvalidDice("weights must not all be zero".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Probability of each face of a single die, indexed by face value
+    fn face_distribution(&self) -> Vec<f64> {
+        let mut faces = vec![0.0];
+        match &self.weights {
+            Some(weights) => {
+                let total: f64 = weights.iter().sum();
+                faces.extend(weights.iter().map(|w| w / total));
+            }
+            None => faces.extend(std::iter::repeat_n(1.0 / self.sides as f64, self.sides)),
+        }
+        faces
+    }
+
+    /// Probability of each roll total, indexed by the total
+    pub(crate) fn distribution(&self) -> Vec<f64> {
+        let faces = self.face_distribution();
+        let mut totals = vec![1.0];
+
+        for _ in 0..self.count {
+            let mut next = vec![0.0; totals.len() + self.sides];
+            for (total, &p) in totals.iter().enumerate().filter(|(_, &p)| p > 0.0) {
+                for (face, &q) in faces.iter().enumerate() {
+                    next[total + face] += p * q;
+                }
+            }
+            totals = next;
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_four_sided_dice_distribution() {
+        let dice = Dice {
+            sides: 4,
+            count: 2,
+            weights: None,
+        };
+        let expected = [0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 3.0, 2.0, 1.0].map(|ways| ways / 16.0);
+        let distribution = dice.distribution();
+        assert_eq!(distribution.len(), expected.len());
+        for (p, q) in distribution.iter().zip(expected) {
+            assert!((p - q).abs() < 1e-15, "{} != {}", p, q);
+        }
+    }
+}
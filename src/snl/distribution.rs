@@ -0,0 +1,137 @@
+use clap::{value_parser, Args};
+use std::{
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{BoardArgs, GameBoard, SnlError, SnlStatistics};
+use crate::stats::{distribution_quantile, SimulationStats};
+use crate::util::print_hms;
+
+const DISTRIBUTION_FILENAME: &str = "snl_distribution.csv";
+const DISTRIBUTION_STATS_FILENAME: &str = "snl_distribution_stats.csv";
+const DISTRIBUTION_QUANTILES_FILENAME: &str = "snl_distribution_quantiles.csv";
+/// Quantiles reported alongside the exact median
+const QUANTILES: [f64; 7] = [0.01, 0.05, 0.25, 0.5, 0.75, 0.95, 0.99];
+
+/// Command line arguments for the exact game length distribution
+#[derive(Args)]
+pub struct DistributionArgs {
+    /// Starting position on the board
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    /// Maximum number of turns to propagate
+    #[arg(long = "max-turns", required = false, value_parser = value_parser!(usize), default_value = "1000")]
+    max_turns: usize,
+
+    /// Stop once the probability of the game still running falls below this
+    #[arg(long = "epsilon", required = false, value_parser = value_parser!(f64), default_value = "1e-12")]
+    epsilon: f64,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl DistributionArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        Ok(())
+    }
+}
+
+impl GameBoard {
+    /// Exact probability that a game from `start_position` ends on each turn
+    ///
+    /// Repeatedly multiplies the start vector by the transition matrix, so `pmf[k]` is the
+    /// probability of reaching the final square on turn `k`. Propagation stops after
+    /// `max_turns` turns or once less than `epsilon` of the probability is still in play.
+    pub(super) fn turn_distribution(
+        &self,
+        start_position: usize,
+        max_turns: usize,
+        epsilon: f64,
+    ) -> Result<Vec<f64>, SnlError> {
+        if start_position >= self.size {
+            return Err(SnlError::InvalidPosition(start_position));
+        }
+
+        let mut state = vec![0.0; self.size + 1];
+        state[start_position] = 1.0;
+        let mut pmf = vec![0.0];
+        let mut remaining = 1.0;
+
+        while pmf.len() <= max_turns && remaining >= epsilon {
+            let mut next = vec![0.0; self.size + 1];
+            for (i, &p) in state.iter().enumerate() {
+                if p > 0.0 {
+                    for (j, q) in self.transition_matrix.row(i) {
+                        next[j] += p * q;
+                    }
+                }
+            }
+            pmf.push(std::mem::take(&mut next[self.size]));
+            remaining = next.iter().sum();
+            state = next;
+        }
+
+        Ok(pmf)
+    }
+}
+
+/// Compute the exact game length distribution from one starting square and save it to CSV
+pub fn snl_distribution(args: DistributionArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {}", board.name);
+    let start_position = board.follow(args.start);
+    if start_position == board.size {
+        println!("Every game from square {} finishes at once", args.start);
+        return Ok(());
+    }
+
+    let start_time = Instant::now();
+    let pmf = board.turn_distribution(start_position, args.max_turns, args.epsilon)?;
+    print_hms(&start_time);
+
+    let mut file = File::create(DISTRIBUTION_FILENAME)?;
+    writeln!(file, "turn,pmf,cdf")?;
+    let mut cdf = 0.0;
+    for (turn, p) in pmf.iter().enumerate().skip(1) {
+        cdf += p;
+        writeln!(file, "{},{},{}", turn, p, cdf)?;
+    }
+    println!("Probability still running after {} turns: {}", pmf.len() - 1, 1.0 - cdf);
+    // Statistics and quantiles alike are taken over the games that finish within the
+    // propagated turns, so they agree with each other however the tail was cut off
+    if 1.0 - cdf >= args.epsilon {
+        println!(
+            "Statistics and quantiles are conditional on finishing within {} turns",
+            pmf.len() - 1
+        );
+    }
+
+    let stats = SnlStatistics {
+        start: args.start,
+        statistics: SimulationStats::from_distribution(&pmf)?,
+        metrics: Vec::new(),
+    };
+    stats.print();
+    let mut file = File::create(DISTRIBUTION_QUANTILES_FILENAME)?;
+    writeln!(file, "quantile,turns")?;
+    for quantile in QUANTILES {
+        let turns = distribution_quantile(&pmf, quantile * cdf).unwrap_or(pmf.len() as u64 - 1);
+        println!("Quantile {}: {}", quantile, turns);
+        writeln!(file, "{},{}", quantile, turns)?;
+    }
+
+    let mut file = File::create(DISTRIBUTION_STATS_FILENAME)?;
+    writeln!(file, "{}", stats.header())?;
+    stats.write_to_csv(&mut file)?;
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use clap::{value_parser, Args};
+use std::{
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{
+    sparse::SparseMatrix, BoardArgs, GameBoard, SnlError, SnlStatistics, CERTAINTY_TOLERANCE,
+};
+use crate::stats::SimulationStats;
+use crate::util::print_hms;
+
+const EXACT_FILENAME: &str = "snl_exact.csv";
+
+/// Command line arguments for the exact Markov chain analysis
+#[derive(Args)]
+pub struct ExactArgs {
+    /// Starting position whose full report is printed
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+/// Transient block of the board's absorbing Markov chain
+#[derive(Debug)]
+pub(super) struct AbsorbingChain {
+    /// Board square of each transient state, in increasing order
+    pub(super) squares: Vec<usize>,
+    /// Transient state of each board square, if it is one
+    index: Vec<Option<usize>>,
+    /// Transition probabilities between transient states
+    pub(super) q: SparseMatrix,
+}
+
+impl AbsorbingChain {
+    /// Exact factorial moments E[T], E[T(T-1)], E[T(T-1)(T-2)] and E[T(T-1)(T-2)(T-3)]
+    /// of the turns to finish from each transient square, computed as k! Q^(k-1) N^k 1
+    ///
+    /// Each product with the fundamental matrix N = (I - Q)^-1 is a sparse solve, so N
+    /// itself is never formed.
+    pub(super) fn factorial_moments(&self) -> Result<Vec<[f64; 4]>, SnlError> {
+        let mut moments = vec![[0.0; 4]; self.squares.len()];
+        let mut term = self.expected_turns()?;
+        let mut factorial = 1.0;
+
+        for k in 0..4 {
+            factorial *= (k + 1) as f64;
+            for (moment, &x) in moments.iter_mut().zip(term.iter()) {
+                moment[k] = factorial * x;
+            }
+            term = self.q.solve_absorbing(&self.q.multiply(&term))?;
+        }
+        Ok(moments)
+    }
+
+    /// Row `row` of the fundamental matrix N = (I - Q)^-1, where N[i][j] is the expected
+    /// number of turns spent on square j before finishing when starting from square i
+    ///
+    /// The row solves (I - Q)^T n = e_row, so it costs one sparse solve.
+    pub(super) fn fundamental_row(&self, row: usize) -> Result<Vec<f64>, SnlError> {
+        let mut unit = vec![0.0; self.squares.len()];
+        unit[row] = 1.0;
+        self.q.transpose().solve_absorbing(&unit)
+    }
+
+    /// Expected turns to finish from each transient square, solving (I - Q) t = 1
+    pub(super) fn expected_turns(&self) -> Result<Vec<f64>, SnlError> {
+        self.q.solve_absorbing(&vec![1.0; self.squares.len()])
+    }
+
+    /// Transient state of `square`, if it is one
+    pub(super) fn position(&self, square: usize) -> Option<usize> {
+        self.index.get(square).copied().flatten()
+    }
+
+    /// Entries of a row over board squares that fall on transient states, re-indexed by state
+    pub(super) fn restrict<'a>(
+        &'a self,
+        row: impl IntoIterator<Item = (usize, f64)> + 'a,
+    ) -> impl Iterator<Item = (usize, f64)> + 'a {
+        row.into_iter()
+            .filter_map(|(square, p)| self.position(square).map(|j| (j, p)))
+    }
+}
+
+impl GameBoard {
+    /// Restrict the transition matrix to the squares a token can rest on before finishing
+    pub(super) fn absorbing_chain(&self) -> AbsorbingChain {
+        let squares: Vec<usize> = (0..self.size)
+            .filter(|square| !self.transitions.contains_key(square))
+            .collect();
+        let mut index = vec![None; self.size + 1];
+        for (i, &square) in squares.iter().enumerate() {
+            index[square] = Some(i);
+        }
+        let mut chain = AbsorbingChain {
+            squares,
+            index,
+            q: SparseMatrix::default(),
+        };
+        chain.q = SparseMatrix::from_rows(
+            chain
+                .squares
+                .iter()
+                .map(|&i| chain.restrict(self.transition_matrix.row(i)).collect::<Vec<_>>()),
+        );
+        chain
+    }
+
+    /// Exact expected number of turns to finish from `square`
+    pub(super) fn expected_turns(&self, square: usize) -> Result<f64, SnlError> {
+        let chain = self.absorbing_chain();
+        let index = chain.position(square).ok_or(SnlError::InvalidPosition(square))?;
+        Ok(chain.expected_turns()?[index])
+    }
+
+    /// Shortest game, its probability and the median game length from every square
+    ///
+    /// The probability of having finished within k turns is the same product for every
+    /// start, P^k applied to the final square's indicator, so one backward pass over the
+    /// sparse matrix serves all squares until each has passed one half.
+    fn finish_profiles(&self) -> Vec<Option<(u64, f64, u64)>> {
+        let mut finished = vec![0.0; self.size + 1];
+        finished[self.size] = 1.0;
+        let mut shortest = vec![None; self.size + 1];
+        let mut median = vec![None; self.size + 1];
+        let squares: Vec<usize> =
+            (0..self.size).filter(|s| !self.transitions.contains_key(s)).collect();
+        let mut pending = squares.len();
+
+        let mut turns = 0;
+        while pending > 0 {
+            turns += 1;
+            finished = self.transition_matrix.multiply(&finished);
+            for &square in &squares {
+                if shortest[square].is_none() && finished[square] > 0.0 {
+                    shortest[square] = Some((turns, finished[square]));
+                }
+                if median[square].is_none() && finished[square] >= 0.5 {
+                    median[square] = Some(turns);
+                    pending -= 1;
+                }
+            }
+        }
+        shortest
+            .into_iter()
+            .zip(median)
+            .map(|(shortest, median)| Some((shortest?.0, shortest?.1, median?)))
+            .collect()
+    }
+
+    /// Exact statistics of the turns to finish from each square a game can start on
+    ///
+    /// Moments come from the fundamental matrix; the shortest game, its probability
+    /// and the median come from the probability of finishing within each number of turns.
+    pub(super) fn exact_statistics(&self) -> Result<Vec<SnlStatistics>, SnlError> {
+        let chain = self.absorbing_chain();
+        let moments = chain.factorial_moments()?;
+        let profiles = self.finish_profiles();
+
+        chain
+            .squares
+            .iter()
+            .zip(moments.iter())
+            .map(|(&square, moments)| {
+                let (min_moves, min_fraction, median) =
+                    profiles[square].ok_or(SnlError::InvalidPosition(square))?;
+                // A game whose shortest length is certain has no spread to take moments of
+                if min_fraction > 1.0 - CERTAINTY_TOLERANCE {
+                    return Ok(SnlStatistics {
+                        start: square,
+                        statistics: SimulationStats::certain(min_moves, None),
+                        metrics: Vec::new(),
+                    });
+                }
+                Ok(SnlStatistics {
+                    start: square,
+                    statistics: SimulationStats::from_factorial_moments(
+                        min_moves,
+                        min_fraction,
+                        moments,
+                        median as f64,
+                    )?,
+                    metrics: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl ExactArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        Ok(())
+    }
+}
+
+/// Compute exact game length statistics for every starting square and save them to CSV
+pub fn snl_exact(args: ExactArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {}", board.name);
+    let start_position = board.follow(args.start);
+    if start_position == board.size {
+        println!("Every game from square {} finishes at once", args.start);
+    }
+
+    let start_time = Instant::now();
+    let all_stats = board.exact_statistics()?;
+    print_hms(&start_time);
+
+    let mut file = File::create(EXACT_FILENAME)?;
+    for (i, stats) in all_stats.iter().enumerate() {
+        if i == 0 {
+            writeln!(file, "{}", stats.header())?;
+        }
+        stats.write_to_csv(&mut file)?;
+        if stats.start == start_position {
+            stats.print();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snl::{Rules, STANDARD_GRID};
+
+    fn standard_board() -> GameBoard {
+        let transitions = GameBoard::create_snakes_and_ladders_map();
+        GameBoard::new(String::new(), STANDARD_GRID, transitions, Rules::default()).unwrap()
+    }
+
+    #[test]
+    fn standard_board_expected_length() {
+        let turns = standard_board().expected_turns(0).unwrap();
+        assert!((turns - 35.8349).abs() < 1e-4, "{}", turns);
+    }
+
+    #[test]
+    fn factorial_moments_match_the_distribution() {
+        let board = standard_board();
+        let chain = board.absorbing_chain();
+        let moments = chain.factorial_moments().unwrap()[chain.position(0).unwrap()];
+        let pmf = board.turn_distribution(0, usize::MAX, 1e-15).unwrap();
+
+        let mut falling = [0.0; 4];
+        for (k, p) in pmf.iter().enumerate() {
+            let mut term = *p;
+            for (j, moment) in falling.iter_mut().enumerate() {
+                term *= k as f64 - j as f64;
+                *moment += term;
+            }
+        }
+        for (exact, propagated) in moments.iter().zip(falling) {
+            assert!((exact - propagated).abs() < 1e-9 * exact, "{} != {}", exact, propagated);
+        }
+    }
+}
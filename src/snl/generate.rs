@@ -0,0 +1,257 @@
+use clap::{value_parser, Args};
+use rand::prelude::*;
+use rayon::prelude::*;
+use std::{collections::HashSet, path::PathBuf, time::Instant};
+
+use super::{board_file::BoardFile, grid::Grid, GameBoard, Rules, RuleArgs, SnlError};
+use crate::util::print_hms;
+
+const GENERATED_BOARD_NAME: &str = "Generated";
+/// Fraction of the target length used as the starting annealing temperature
+const INITIAL_TEMPERATURE: f64 = 0.1;
+
+/// Command line arguments for the random board generator
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Board file to write (.toml or .json)
+    #[arg(short = 'o', long = "output", required = true)]
+    output: PathBuf,
+
+    /// Expected game length to aim for, in turns from the start
+    #[arg(long = "target", required = true, value_parser = value_parser!(f64))]
+    target: f64,
+
+    /// Accept any layout whose expected length is within this many turns of the target
+    #[arg(long = "tolerance", required = false, value_parser = value_parser!(f64), default_value = "1")]
+    tolerance: f64,
+
+    /// Board grid as <rows>x<columns>, numbered back and forth from the bottom left
+    #[arg(long = "grid", required = false, default_value = "10x10")]
+    grid: Grid,
+
+    /// Number of snakes to place
+    #[arg(long = "snakes", required = false, value_parser = value_parser!(usize), default_value = "8")]
+    snakes: usize,
+
+    /// Number of ladders to place
+    #[arg(long = "ladders", required = false, value_parser = value_parser!(usize), default_value = "8")]
+    ladders: usize,
+
+    /// Independent annealing runs, each from its own random layout
+    #[arg(long = "restarts", required = false, value_parser = value_parser!(usize), default_value = "8")]
+    restarts: usize,
+
+    /// Annealing steps per run
+    #[arg(long = "steps", required = false, value_parser = value_parser!(usize), default_value = "2000")]
+    steps: usize,
+
+    /// Seed for reproducible layouts
+    #[arg(long = "seed", required = false, value_parser = value_parser!(u64))]
+    seed: Option<u64>,
+
+    /// Name stored in the board file
+    #[arg(long = "name", required = false)]
+    name: Option<String>,
+
+    #[command(flatten)]
+    rules: RuleArgs,
+}
+
+impl GenerateArgs {
+    /// Number of squares on the board
+    fn size(&self) -> usize {
+        self.grid.size()
+    }
+
+    /// Validate command line arguments
+    fn validate(&self) -> Result<(), SnlError> {
+        let invalid = |reason: String| Err(SnlError::InvalidGenerator(reason));
+        if !(self.target.is_finite() && self.target > 0.0) {
+            return invalid(format!("target length {} must be positive", self.target));
+        }
+        if !(self.tolerance.is_finite() && self.tolerance >= 0.0) {
+            return invalid(format!("tolerance {} must be non-negative", self.tolerance));
+        }
+        if self.size() < 3 {
+            return invalid(format!("board size {} is too small", self.size()));
+        }
+        // Every end of every snake and ladder needs a square of its own
+        if 2 * (self.snakes + self.ladders) >= self.size() {
+            return invalid(format!(
+                "{} snakes and {} ladders do not fit on {} squares",
+                self.snakes,
+                self.ladders,
+                self.size()
+            ));
+        }
+        if self.restarts == 0 || self.steps == 0 {
+            return invalid("restarts and steps must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Snakes and ladders as `(start, end)` pairs, with no square used twice
+#[derive(Clone, Debug)]
+struct Layout {
+    snakes: Vec<(usize, usize)>,
+    ladders: Vec<(usize, usize)>,
+}
+
+impl Layout {
+    /// Place `snakes` snakes and `ladders` ladders uniformly at random
+    fn random<R: Rng>(rng: &mut R, size: usize, snakes: usize, ladders: usize) -> Self {
+        let mut layout = Layout {
+            snakes: Vec::with_capacity(snakes),
+            ladders: Vec::with_capacity(ladders),
+        };
+        for _ in 0..snakes {
+            let snake = layout.sample_piece(rng, size, true);
+            layout.snakes.push(snake);
+        }
+        for _ in 0..ladders {
+            let ladder = layout.sample_piece(rng, size, false);
+            layout.ladders.push(ladder);
+        }
+        layout
+    }
+
+    /// Copy of the layout with one snake or ladder moved to a new random place
+    fn neighbour<R: Rng>(&self, rng: &mut R, size: usize) -> Self {
+        let mut layout = self.clone();
+        let index = rng.gen_range(0..self.snakes.len() + self.ladders.len());
+        if index < layout.snakes.len() {
+            layout.snakes.swap_remove(index);
+            let snake = layout.sample_piece(rng, size, true);
+            layout.snakes.push(snake);
+        } else {
+            layout.ladders.swap_remove(index - self.snakes.len());
+            let ladder = layout.sample_piece(rng, size, false);
+            layout.ladders.push(ladder);
+        }
+        layout
+    }
+
+    /// Sample a snake or ladder whose ends are both on free squares
+    ///
+    /// Square 0 and the final square are never the start of a piece, and keeping every
+    /// end distinct rules out chains, so any layout is a valid board file.
+    fn sample_piece<R: Rng>(&self, rng: &mut R, size: usize, snake: bool) -> (usize, usize) {
+        let used: HashSet<usize> = self
+            .snakes
+            .iter()
+            .chain(self.ladders.iter())
+            .flat_map(|&(start, end)| [start, end])
+            .collect();
+        loop {
+            let piece = if snake {
+                let head = rng.gen_range(2..size);
+                (head, rng.gen_range(1..head))
+            } else {
+                let base = rng.gen_range(1..size - 1);
+                (base, rng.gen_range(base + 1..=size))
+            };
+            if !used.contains(&piece.0) && !used.contains(&piece.1) {
+                return piece;
+            }
+        }
+    }
+
+    /// Exact expected game length from square 0, or infinity if the finish is unreachable
+    fn expected_turns(&self, grid: Grid, rules: &Rules) -> Result<f64, SnlError> {
+        let transitions = self.snakes.iter().chain(self.ladders.iter()).copied().collect();
+        match GameBoard::new(String::new(), grid, transitions, rules.clone())
+            .and_then(|board| board.expected_turns(0))
+        {
+            Err(SnlError::UnreachableFinish(_) | SnlError::SingularMatrix) => Ok(f64::INFINITY),
+            result => result,
+        }
+    }
+}
+
+/// Simulated annealing on the distance between a layout's expected length and the target
+///
+/// Each step moves one snake or ladder; worse layouts are accepted with a probability
+/// that shrinks as the temperature falls linearly to zero. Returns the closest layout
+/// seen and its expected length, stopping early once it is within tolerance.
+fn anneal<R: Rng>(
+    rng: &mut R,
+    args: &GenerateArgs,
+    rules: &Rules,
+) -> Result<(Layout, f64), SnlError> {
+    let mut current = Layout::random(rng, args.size(), args.snakes, args.ladders);
+    let mut current_turns = current.expected_turns(args.grid, rules)?;
+    let mut best = (current.clone(), current_turns);
+
+    for step in 0..args.steps {
+        if (best.1 - args.target).abs() <= args.tolerance {
+            break;
+        }
+        let temperature =
+            INITIAL_TEMPERATURE * args.target * (1.0 - step as f64 / args.steps as f64);
+        let candidate = current.neighbour(rng, args.size());
+        let candidate_turns = candidate.expected_turns(args.grid, rules)?;
+        let change = (candidate_turns - args.target).abs() - (current_turns - args.target).abs();
+
+        if change <= 0.0 || rng.gen::<f64>() < (-change / temperature).exp() {
+            current = candidate;
+            current_turns = candidate_turns;
+            if (current_turns - args.target).abs() < (best.1 - args.target).abs() {
+                best = (current.clone(), current_turns);
+            }
+        }
+    }
+    Ok(best)
+}
+
+/// Search for a random board with the target expected length and write it as a board file
+pub fn snl_generate(args: GenerateArgs) -> Result<(), SnlError> {
+    args.validate()?;
+    let rules = args.rules.rules(None);
+    rules.dice.validate()?;
+
+    let start_time = Instant::now();
+    let runs = (0..args.restarts)
+        .into_par_iter()
+        .map(|run| {
+            let mut rng = match args.seed {
+                Some(seed) => StdRng::seed_from_u64(seed.wrapping_add(run as u64)),
+                None => StdRng::from_entropy(),
+            };
+            anneal(&mut rng, &args, &rules)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    print_hms(&start_time);
+
+    let (mut layout, turns) = runs
+        .into_iter()
+        .min_by(|a, b| (a.1 - args.target).abs().total_cmp(&(b.1 - args.target).abs()))
+        .ok_or_else(|| SnlError::InvalidGenerator("no runs".to_string()))?;
+    if (turns - args.target).abs() > args.tolerance {
+        println!(
+            "No layout within {} turns of the target {}; writing the closest found",
+            args.tolerance, args.target
+        );
+    }
+
+    layout.snakes.sort_unstable();
+    layout.ladders.sort_unstable();
+    let board = BoardFile {
+        name: Some(args.name.clone().unwrap_or_else(|| GENERATED_BOARD_NAME.to_string())),
+        size: None,
+        rows: Some(args.grid.rows),
+        columns: Some(args.grid.columns),
+        snakes: layout.snakes,
+        ladders: layout.ladders,
+        // The layout was scored under these rules, so the file keeps any that were chosen
+        chaining: args.rules.chaining,
+        overshoot: args.rules.overshoot,
+        roll_again: args.rules.roll_again,
+        dice: args.rules.overrides_dice().then_some(rules.dice),
+    };
+    board.save(&args.output)?;
+    println!("Expected turns: {}", turns);
+    println!("Board written to {}", args.output.display());
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::{fmt, str::FromStr};
+
+/// Layout of the squares as a grid numbered boustrophedon style
+///
+/// Square 1 is in the bottom left corner. Numbers run left to right along the bottom
+/// row, then right to left along the row above, and so on, so the final square is in
+/// the top row. Square 0 is the off-board start.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid {
+    pub(crate) rows: usize,
+    pub(crate) columns: usize,
+}
+
+impl Grid {
+    /// Most nearly square grid with exactly `size` squares, e.g. 10 × 10 for 100 or
+    /// 5 × 6 for 30; a prime size gives a single row
+    pub(crate) fn for_size(size: usize) -> Self {
+        let rows = (1..=size)
+            .take_while(|rows| rows * rows <= size)
+            .filter(|rows| size.is_multiple_of(*rows))
+            .last()
+            .unwrap_or(1);
+        Grid {
+            rows,
+            columns: size / rows.max(1),
+        }
+    }
+
+    /// Number of squares on the board, excluding the off-board start
+    pub(crate) fn size(&self) -> usize {
+        self.rows * self.columns
+    }
+
+    /// Row and column of `square`, counting rows up from the bottom and columns from the
+    /// left, both from 0, or `None` for the off-board start and squares past the end
+    pub(crate) fn cell(&self, square: usize) -> Option<(usize, usize)> {
+        if square == 0 || square > self.size() {
+            return None;
+        }
+        let row = (square - 1) / self.columns;
+        let offset = (square - 1) % self.columns;
+        let column = if row.is_multiple_of(2) {
+            offset
+        } else {
+            self.columns - 1 - offset
+        };
+        Some((row, column))
+    }
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    /// Parse `<rows>x<columns>`, e.g. `12x12` or `20x5`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rows, columns) = s
+            .split_once(['x', 'X', '×'])
+            .ok_or_else(|| format!("expected <rows>x<columns>, found `{}`", s))?;
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("`{}` is not a positive whole number", n))
+        };
+        Ok(Grid {
+            rows: parse(rows)?,
+            columns: parse(columns)?,
+        })
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.rows, self.columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_run_back_and_forth_from_the_bottom_left() {
+        let grid = Grid { rows: 10, columns: 10 };
+        assert_eq!(grid.cell(0), None);
+        assert_eq!(grid.cell(1), Some((0, 0)));
+        assert_eq!(grid.cell(10), Some((0, 9)));
+        assert_eq!(grid.cell(11), Some((1, 9)));
+        assert_eq!(grid.cell(20), Some((1, 0)));
+        assert_eq!(grid.cell(21), Some((2, 0)));
+        assert_eq!(grid.cell(100), Some((9, 0)));
+        assert_eq!(grid.cell(101), None);
+    }
+}
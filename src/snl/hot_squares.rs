@@ -0,0 +1,188 @@
+use clap::{value_parser, Args};
+use rayon::prelude::*;
+use std::{
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{exact::AbsorbingChain, sparse::SparseMatrix, BoardArgs, GameBoard, SnlError};
+use crate::util::print_hms;
+
+const HOT_SQUARES_FILENAME: &str = "snl_hot_squares.csv";
+const FEATURE_USES_FILENAME: &str = "snl_feature_uses.csv";
+
+/// Command line arguments for the expected visits report
+#[derive(Args)]
+pub struct HotSquaresArgs {
+    /// Starting position on the board
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl HotSquaresArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        Ok(())
+    }
+}
+
+/// Expected traffic on one square over a game
+#[derive(Debug)]
+pub(super) struct SquareVisits {
+    pub(super) square: usize,
+    /// Expected number of turns begun on the square, from the fundamental matrix
+    turns: f64,
+    /// Expected number of rolls landing on the square
+    pub(super) landings: f64,
+    /// Probability that some roll lands on the square at least once
+    pub(super) probability: f64,
+}
+
+impl GameBoard {
+    /// Expected visits and landings for every square of a game from `start_position`
+    ///
+    /// Landings are summed over the turns begun on each square, weighted by the
+    /// fundamental matrix. The probability of ever landing on a square comes from
+    /// solving for the chance of a turn landing there before the game moves on, which
+    /// takes one sparse solve per square, so those solves run in parallel.
+    pub(super) fn square_visits(&self, start_position: usize) -> Result<Vec<SquareVisits>, SnlError> {
+        let chain = self.absorbing_chain();
+        let row = chain
+            .position(start_position)
+            .ok_or(SnlError::InvalidPosition(start_position))?;
+        let fundamental = chain.fundamental_row(row)?;
+        let rolls = self.rules.dice.distribution();
+
+        let mut turns = vec![0.0; self.size + 1];
+        let mut landings = vec![0.0; self.size + 1];
+        for (&square, &visits) in chain.squares.iter().zip(fundamental.iter()) {
+            turns[square] = visits;
+            let turn = self.resolve_turn(square, &rolls, None);
+            for (landed, p) in turn.landings {
+                landings[landed] += visits * p;
+            }
+        }
+
+        (1..=self.size)
+            .into_par_iter()
+            .map(|square| {
+                let probability = if landings[square] > 0.0 {
+                    self.landing_probability(&chain, square, &rolls)?[row]
+                } else {
+                    0.0
+                };
+                Ok(SquareVisits {
+                    square,
+                    turns: turns[square],
+                    landings: landings[square],
+                    probability,
+                })
+            })
+            .collect()
+    }
+
+    /// Probability of some roll landing on `target` from each transient square of `chain`
+    fn landing_probability(
+        &self,
+        chain: &AbsorbingChain,
+        target: usize,
+        rolls: &[f64],
+    ) -> Result<Vec<f64>, SnlError> {
+        let mut rows = Vec::with_capacity(chain.squares.len());
+        let mut hit = Vec::with_capacity(chain.squares.len());
+        for &square in &chain.squares {
+            let turn = self.resolve_turn(square, rolls, Some(target));
+            hit.push(1.0 - turn.outcomes.iter().map(|&(_, p)| p).sum::<f64>());
+            rows.push(chain.restrict(turn.outcomes).collect::<Vec<_>>());
+        }
+        SparseMatrix::from_rows(rows).solve_absorbing(&hit)
+    }
+}
+
+/// Report expected visits per square and uses of each snake and ladder, and save them to CSV
+pub fn snl_hot_squares(args: HotSquaresArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {} ({} grid)", board.name, board.grid);
+    let start_position = board.follow(args.start);
+    if start_position == board.size {
+        println!("Every game from square {} finishes at once", args.start);
+        return Ok(());
+    }
+
+    let start_time = Instant::now();
+    let visits = board.square_visits(start_position)?;
+    print_hms(&start_time);
+
+    // Rows count up from the bottom and columns from the left, both from 1
+    let cell = |square: usize| {
+        let (row, column) = board.grid.cell(square).unwrap_or_default();
+        (row + 1, column + 1)
+    };
+    let mut file = File::create(HOT_SQUARES_FILENAME)?;
+    writeln!(file, "square,row,column,turns,landings,landing_probability")?;
+    for square in &visits {
+        let (row, column) = cell(square.square);
+        writeln!(
+            file,
+            "{},{},{},{},{},{}",
+            square.square, row, column, square.turns, square.landings, square.probability
+        )?;
+    }
+
+    let mut ranked: Vec<&SquareVisits> = visits
+        .iter()
+        .filter(|square| {
+            square.square < board.size && !board.transitions.contains_key(&square.square)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.landings.total_cmp(&a.landings));
+    println!("Hot squares:");
+    println!(
+        "{:>6} {:>4} {:>6} {:>10} {:>10} {:>12}",
+        "square", "row", "column", "turns", "landings", "probability"
+    );
+    for square in ranked {
+        let (row, column) = cell(square.square);
+        println!(
+            "{:>6} {:>4} {:>6} {:>10.4} {:>10.4} {:>12.4}",
+            square.square, row, column, square.turns, square.landings, square.probability
+        );
+    }
+
+    // Board validation rejects a snake or ladder from square 0, but one there would be taken
+    // exactly once, at the start, as square 0 is never landed on
+    let mut features: Vec<(usize, usize, f64, f64)> = board
+        .transitions
+        .iter()
+        .map(|(&from, &to)| match from {
+            0 => (from, to, 1.0, 1.0),
+            _ => (from, to, visits[from - 1].landings, visits[from - 1].probability),
+        })
+        .collect();
+    features.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+    let mut file = File::create(FEATURE_USES_FILENAME)?;
+    writeln!(file, "kind,from,to,expected_uses,use_probability")?;
+    println!("Snakes and ladders:");
+    println!("{:<6} {:>9} {:>10} {:>12}", "kind", "feature", "uses", "probability");
+    for (from, to, uses, probability) in features {
+        let kind = if to < from { "snake" } else { "ladder" };
+        println!(
+            "{:<6} {:>9} {:>10.4} {:>12.4}",
+            kind,
+            format!("{}->{}", from, to),
+            uses,
+            probability
+        );
+        writeln!(file, "{},{},{},{},{}", kind, from, to, uses, probability)?;
+    }
+
+    Ok(())
+}
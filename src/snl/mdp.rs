@@ -0,0 +1,288 @@
+use clap::{value_parser, Args, ValueEnum};
+use rand::{distributions::WeightedIndex, prelude::*};
+use rayon::prelude::*;
+use std::{
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{BoardArgs, GameBoard, RollAgain, SnlError};
+use crate::stats::{SimulationError, SimulationStats};
+use crate::util::print_hms;
+
+const MDP_FILENAME: &str = "snl_mdp.csv";
+const POLICY_FILENAME: &str = "snl_policy.csv";
+
+/// Decision a player makes after seeing the dice each turn
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Choice {
+    /// Move forward or backward by the roll; backward moves may not pass square 0
+    Direction,
+    /// Roll two sets of dice and move by either one
+    Dice,
+}
+
+/// Command line arguments for the optimal strategy solver
+#[derive(Args)]
+pub struct MdpArgs {
+    /// Choice the player makes each turn
+    #[arg(long = "choice", required = true, value_enum)]
+    choice: Choice,
+
+    /// Number of games to simulate with each policy
+    #[arg(short = 'n', required = false, value_parser = value_parser!(u64), default_value = "100000")]
+    num_iterations: u64,
+
+    /// Starting position on the board
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    /// Stop value iteration once no value changes by more than this
+    #[arg(long = "tolerance", required = false, value_parser = value_parser!(f64), default_value = "1e-10")]
+    tolerance: f64,
+
+    /// Give up if value iteration has not converged after this many sweeps
+    #[arg(long = "max-iterations", required = false, value_parser = value_parser!(usize), default_value = "100000")]
+    max_iterations: usize,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl MdpArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.num_iterations == 0 {
+            return Err(SimulationError::InvalidIterationCount(self.num_iterations).into());
+        }
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        // Bonus rolls would make the choice part of a multi-roll turn
+        if board.rules.roll_again != RollAgain::Off {
+            return Err(SnlError::UnsupportedRule(
+                "player choices cannot be combined with --roll-again".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A random outcome of the dice and the signed moves the player may pick from
+#[derive(Debug)]
+struct Outcome {
+    probability: f64,
+    /// How the dice fell, e.g. `4` or `3+5`
+    label: String,
+    moves: Vec<isize>,
+}
+
+/// Optimal expected turns to finish from each square, and the move to make for each outcome
+#[derive(Debug)]
+struct Solution {
+    values: Vec<f64>,
+    /// Index into the outcome's moves of the best move, for each square and outcome
+    policy: Vec<Vec<usize>>,
+    sweeps: usize,
+}
+
+impl GameBoard {
+    /// Every outcome of a turn's dice under `choice`, with the moves it allows
+    fn choice_outcomes(&self, choice: Choice) -> Vec<Outcome> {
+        let rolls = self.rules.dice.distribution();
+        let possible = || rolls.iter().enumerate().filter(|(_, &p)| p > 0.0);
+        match choice {
+            Choice::Direction => possible()
+                .map(|(roll, &p)| Outcome {
+                    probability: p,
+                    label: roll.to_string(),
+                    moves: vec![roll as isize, -(roll as isize)],
+                })
+                .collect(),
+            Choice::Dice => possible()
+                .flat_map(|(first, &p)| {
+                    possible().map(move |(second, &q)| Outcome {
+                        probability: p * q,
+                        label: format!("{}+{}", first, second),
+                        moves: if first == second {
+                            vec![first as isize]
+                        } else {
+                            vec![first as isize, second as isize]
+                        },
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Square reached by moving `step` squares from `square`, or `None` if a backward
+    /// move would pass square 0
+    fn choice_destination(&self, square: usize, step: isize) -> Option<usize> {
+        let landed = if step >= 0 {
+            self.rules.overshoot.apply(square, square + step as usize, self.size)
+        } else {
+            square.checked_sub(step.unsigned_abs())?
+        };
+        Some(self.follow(landed))
+    }
+
+    /// Value iteration for the policy that minimises the expected number of turns
+    ///
+    /// Values start at zero and sweep in place until no square changes by more than
+    /// `tolerance`. A move that is not allowed is never chosen.
+    fn solve_choice(
+        &self,
+        outcomes: &[Outcome],
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Result<Solution, SnlError> {
+        let squares: Vec<usize> = (0..self.size)
+            .filter(|square| !self.transitions.contains_key(square))
+            .collect();
+        let destinations: Vec<Vec<Vec<Option<usize>>>> = (0..self.size)
+            .map(|square| {
+                outcomes
+                    .iter()
+                    .map(|outcome| {
+                        outcome
+                            .moves
+                            .iter()
+                            .map(|&step| self.choice_destination(square, step))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        let best = |values: &[f64], options: &[Option<usize>]| {
+            options
+                .iter()
+                .enumerate()
+                .filter_map(|(i, destination)| destination.map(|square| (i, values[square])))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap_or((0, f64::INFINITY))
+        };
+
+        let mut values = vec![0.0; self.size + 1];
+        for sweep in 1..=max_iterations {
+            let mut change: f64 = 0.0;
+            for &square in &squares {
+                let value = 1.0
+                    + outcomes
+                        .iter()
+                        .zip(destinations[square].iter())
+                        .map(|(outcome, options)| outcome.probability * best(&values, options).1)
+                        .sum::<f64>();
+                change = change.max((value - values[square]).abs());
+                values[square] = value;
+            }
+            if change <= tolerance {
+                let policy = (0..self.size)
+                    .map(|square| {
+                        destinations[square]
+                            .iter()
+                            .map(|options| best(&values, options).0)
+                            .collect()
+                    })
+                    .collect();
+                return Ok(Solution {
+                    values,
+                    policy,
+                    sweeps: sweep,
+                });
+            }
+        }
+        Err(SnlError::NoConvergence(max_iterations))
+    }
+
+    /// Play one game making a choice each turn, following `policy` or else choosing at random
+    /// among the allowed moves, and return the number of turns taken
+    fn play_choice_game<R: Rng>(
+        &self,
+        rng: &mut R,
+        start_position: usize,
+        outcomes: &[Outcome],
+        sampler: &WeightedIndex<f64>,
+        policy: Option<&[Vec<usize>]>,
+    ) -> u64 {
+        let mut turns = 0;
+        let mut position = start_position;
+
+        while position != self.size {
+            turns += 1;
+            let index = sampler.sample(rng);
+            let outcome = &outcomes[index];
+            let step = match policy {
+                Some(policy) => outcome.moves[policy[position][index]],
+                None => *outcome
+                    .moves
+                    .iter()
+                    .filter(|&&step| self.choice_destination(position, step).is_some())
+                    .choose(rng)
+                    .unwrap_or(&outcome.moves[0]),
+            };
+            position = self.choice_destination(position, step).unwrap_or(position);
+        }
+        turns
+    }
+}
+
+/// Find the policy minimising expected turns under a player choice, and compare it with
+/// random play by simulation
+pub fn snl_mdp(args: MdpArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {}", board.name);
+    let start_position = board.follow(args.start);
+    if start_position == board.size {
+        println!("Every game from square {} finishes at once", args.start);
+        return Ok(());
+    }
+
+    let start_time = Instant::now();
+    let outcomes = board.choice_outcomes(args.choice);
+    let solution = board.solve_choice(&outcomes, args.tolerance, args.max_iterations)?;
+    let baseline = board.absorbing_chain();
+    let baseline_values = baseline.expected_turns()?;
+    print_hms(&start_time);
+    println!("Value iteration converged after {} sweeps", solution.sweeps);
+
+    let mut file = File::create(MDP_FILENAME)?;
+    writeln!(file, "square,optimal_expected_turns,expected_turns_without_choice")?;
+    for (&square, &without) in baseline.squares.iter().zip(baseline_values.iter()) {
+        writeln!(file, "{},{},{}", square, solution.values[square], without)?;
+    }
+    let mut file = File::create(POLICY_FILENAME)?;
+    writeln!(file, "square,outcome,move")?;
+    for &square in &baseline.squares {
+        for (outcome, &best) in outcomes.iter().zip(solution.policy[square].iter()) {
+            writeln!(file, "{},{},{:+}", square, outcome.label, outcome.moves[best])?;
+        }
+    }
+
+    let without = baseline
+        .squares
+        .iter()
+        .position(|&square| square == start_position)
+        .map_or(0.0, |i| baseline_values[i]);
+    println!("Optimal expected turns: {}", solution.values[start_position]);
+    println!("Expected turns without the choice: {}", without);
+
+    let probabilities: Vec<f64> = outcomes.iter().map(|outcome| outcome.probability).collect();
+    let sampler = WeightedIndex::new(&probabilities).map_err(|_| SnlError::TransitionMatrixError)?;
+    for (label, policy) in [
+        ("Optimal policy", Some(solution.policy.as_slice())),
+        ("Random policy", None),
+    ] {
+        let moves: Vec<u64> = (0..args.num_iterations)
+            .into_par_iter()
+            .map(|_| {
+                board.play_choice_game(&mut thread_rng(), start_position, &outcomes, &sampler, policy)
+            })
+            .collect();
+        println!("{}:", label);
+        SimulationStats::from_values(&moves)?.print();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use clap::{Args, ValueEnum};
+use rand::prelude::*;
+
+use super::{alias::AliasTable, GameBoard, SnlError};
+
+/// Where the moves of a simulated game come from
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Movement {
+    /// Roll the board's dice
+    #[default]
+    Dice,
+    /// Spin a spinner with weighted sections numbered from 1
+    Spinner,
+    /// Draw move cards without replacement, reshuffling the deck when it runs out
+    Deck,
+}
+
+/// Command line arguments selecting how tokens move in simulated games
+#[derive(Args)]
+pub struct MovementArgs {
+    /// Source of each move; the exact analyses always use the dice
+    #[arg(long = "movement", value_enum, default_value_t = Movement::Dice)]
+    movement: Movement,
+
+    /// Comma separated relative weight of each spinner section, numbered from 1
+    #[arg(long = "spinner", value_delimiter = ',', default_value = "1,1,1,1,1,1")]
+    spinner: Vec<f64>,
+
+    /// Comma separated value of each distinct card in the deck
+    #[arg(long = "deck", value_delimiter = ',', default_value = "1,2,3,4,5,6")]
+    deck: Vec<usize>,
+
+    /// Number of copies of each card in the deck
+    #[arg(long = "deck-copies", default_value = "4")]
+    deck_copies: usize,
+}
+
+impl MovementArgs {
+    /// Move source for games on `board`, checking the spinner or deck given
+    pub(super) fn source(&self, board: &GameBoard) -> Result<MoveSource, SnlError> {
+        match self.movement {
+            Movement::Dice => Ok(board.dice_source()),
+            Movement::Spinner => {
+                if self.spinner.iter().any(|&w| !(w.is_finite() && w >= 0.0)) {
+                    return Err(SnlError::InvalidMovement(
+                        "spinner weights must be non-negative".to_string(),
+                    ));
+                }
+                let weights: Vec<f64> =
+                    std::iter::once(0.0).chain(self.spinner.iter().copied()).collect();
+                MoveSource::weighted(&weights)
+            }
+            Movement::Deck => {
+                if self.deck.contains(&0) {
+                    return Err(SnlError::InvalidMovement(
+                        "cards must move at least one square".to_string(),
+                    ));
+                }
+                if self.deck.is_empty() || self.deck_copies == 0 {
+                    return Err(SnlError::InvalidMovement("the deck must have a card".to_string()));
+                }
+                let cards: Vec<usize> = self
+                    .deck
+                    .iter()
+                    .flat_map(|&card| std::iter::repeat_n(card, self.deck_copies))
+                    .collect();
+                // Start with every card drawn, so the first draw shuffles a fresh deck
+                let drawn = cards.len();
+                Ok(MoveSource::Deck { cards, drawn })
+            }
+        }
+    }
+}
+
+/// Source of the moves for one simulated game
+///
+/// Dice and spinners are independent draws from a fixed distribution, so games played
+/// with them are Markov. A deck remembers which cards have been drawn, so each game
+/// needs its own copy.
+#[derive(Clone, Debug)]
+pub(super) enum MoveSource {
+    /// Independent draws of each total with the weight it has in the table
+    Weighted {
+        table: AliasTable,
+        /// Largest total with nonzero weight
+        highest: usize,
+    },
+    /// Cards dealt in order from `drawn` onwards, shuffled again once all are dealt
+    Deck { cards: Vec<usize>, drawn: usize },
+}
+
+impl MoveSource {
+    /// Independent draws with relative `weights`, indexed by the total
+    pub(super) fn weighted(weights: &[f64]) -> Result<Self, SnlError> {
+        let table = AliasTable::new(weights.iter().copied().enumerate())
+            .ok_or_else(|| SnlError::InvalidMovement("weights must not all be zero".to_string()))?;
+        let highest = weights.iter().rposition(|&w| w > 0.0).unwrap_or(0);
+        Ok(Self::Weighted { table, highest })
+    }
+
+    /// Draw the next move
+    pub(super) fn next_move<R: Rng>(&mut self, rng: &mut R) -> usize {
+        match self {
+            Self::Weighted { table, .. } => table.sample(rng),
+            Self::Deck { cards, drawn } => {
+                if *drawn == cards.len() {
+                    cards.shuffle(rng);
+                    *drawn = 0;
+                }
+                *drawn += 1;
+                cards[*drawn - 1]
+            }
+        }
+    }
+
+    /// Largest move the source can give, which earns a bonus roll under a roll-again rule
+    pub(super) fn highest(&self) -> usize {
+        match self {
+            Self::Weighted { highest, .. } => *highest,
+            Self::Deck { cards, .. } => cards.iter().copied().max().unwrap_or(0),
+        }
+    }
+
+    /// Whether the source can ever give a move of `value`
+    pub(super) fn allows(&self, value: usize) -> bool {
+        match self {
+            Self::Weighted { table, .. } => table.contains(value),
+            Self::Deck { cards, .. } => cards.contains(&value),
+        }
+    }
+}
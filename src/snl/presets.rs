@@ -0,0 +1,103 @@
+use clap::Args;
+use std::collections::HashMap;
+
+use super::{grid::Grid, GameBoard, RuleArgs, Rules, SnlError, STANDARD_GRID};
+
+/// Preset played when no board or preset is chosen
+const DEFAULT_PRESET: &str = "milton-bradley";
+
+/// A well-known board layout that can be chosen by name with `--preset`
+pub(crate) struct Preset {
+    /// Name used on the command line
+    name: &'static str,
+    /// Other names accepted for the same preset
+    aliases: &'static [&'static str],
+    /// Name printed when the board is played
+    title: &'static str,
+    /// Where the layout comes from, printed by `list-boards`
+    source: &'static str,
+    grid: Grid,
+    transitions: fn() -> HashMap<usize, usize>,
+}
+
+/// Registry of built-in layouts
+///
+/// Every layout cites where it comes from. Only layouts that can be checked against a
+/// published source belong here; other editions are played from board files.
+const PRESETS: [Preset; 2] = [
+    Preset {
+        name: "milton-bradley",
+        aliases: &["standard", "chutes-and-ladders"],
+        title: "Milton Bradley Chutes and Ladders",
+        source: "Milton Bradley Chutes and Ladders board, 1952 and later printings",
+        grid: STANDARD_GRID,
+        transitions: GameBoard::create_snakes_and_ladders_map,
+    },
+    Preset {
+        name: "empty",
+        aliases: &[],
+        title: "Empty 10x10",
+        source: "synthetic baseline, not a published board",
+        grid: STANDARD_GRID,
+        transitions: HashMap::new,
+    },
+];
+
+impl Preset {
+    /// Look up a preset by name or alias, ignoring case
+    pub(crate) fn find(name: &str) -> Result<&'static Preset, SnlError> {
+        PRESETS
+            .iter()
+            .find(|preset| {
+                std::iter::once(&preset.name)
+                    .chain(preset.aliases.iter())
+                    .any(|candidate| candidate.eq_ignore_ascii_case(name))
+            })
+            .ok_or_else(|| SnlError::UnknownPreset(name.to_string()))
+    }
+
+    /// The preset played when no board is chosen
+    pub(crate) fn default_preset() -> &'static Preset {
+        Self::find(DEFAULT_PRESET).expect("default preset is registered")
+    }
+
+    /// Build the preset's board under the given rules
+    pub(crate) fn board(&self, rules: Rules) -> Result<GameBoard, SnlError> {
+        GameBoard::new(self.title.to_string(), self.grid, (self.transitions)(), rules)
+    }
+}
+
+/// Command line arguments for listing the built-in boards
+#[derive(Args)]
+pub struct ListBoardsArgs {
+    #[command(flatten)]
+    rules: RuleArgs,
+}
+
+/// Print every preset with its size, number of snakes and ladders, exact expected length
+/// and source
+pub fn list_boards(args: ListBoardsArgs) -> Result<(), SnlError> {
+    println!(
+        "{:<16} {:>6} {:>7} {:>6} {:>7} {:>14}  aliases",
+        "preset", "grid", "squares", "snakes", "ladders", "expected turns"
+    );
+    for preset in &PRESETS {
+        let board = preset.board(args.rules.rules(None))?;
+        let snakes = board.transitions.iter().filter(|(&from, &to)| to < from).count();
+        let ladders = board.transitions.len() - snakes;
+        let expected = board.expected_turns(board.follow(0))?;
+        let line = format!(
+            "{:<16} {:>6} {:>7} {:>6} {:>7} {:>14.4}  {}",
+            preset.name,
+            preset.grid.to_string(),
+            board.size,
+            snakes,
+            ladders,
+            expected,
+            preset.aliases.join(", ")
+        );
+        println!("{}", line.trim_end());
+        println!("{:<16} source: {}", "", preset.source);
+    }
+    Ok(())
+}
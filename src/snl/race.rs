@@ -0,0 +1,218 @@
+use clap::{value_parser, Args, ValueEnum};
+use rand::thread_rng;
+use rayon::prelude::*;
+use std::{
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{BoardArgs, GameBoard, SnlError};
+use crate::stats::{SimulationError, SimulationStats};
+use crate::util::print_hms;
+
+const RACE_FILENAME: &str = "snl_race.csv";
+/// Remaining probability at which the exact win probabilities stop propagating
+const RACE_EPSILON: f64 = 1e-12;
+
+/// What happens when a token ends its turn on a square occupied by an opponent
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Collision {
+    /// Tokens share squares freely
+    #[default]
+    None,
+    /// The opponent already on the square is sent back to the start
+    SendHome,
+}
+
+/// Command line arguments for the multi-player race
+#[derive(Args)]
+pub struct RaceArgs {
+    /// Number of races to simulate
+    #[arg(short = 'n', required = true, value_parser = value_parser!(u64))]
+    num_iterations: u64,
+
+    /// Number of players, seated in turn order
+    #[arg(long = "players", required = false, value_parser = value_parser!(usize), default_value = "2")]
+    players: usize,
+
+    /// Rule for landing on a square occupied by an opponent
+    #[arg(long = "collision", value_enum, default_value_t = Collision::None)]
+    collision: Collision,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl RaceArgs {
+    /// Validate command line arguments
+    fn validate(&self) -> Result<(), SnlError> {
+        if self.num_iterations == 0 {
+            return Err(SimulationError::InvalidIterationCount(self.num_iterations).into());
+        }
+        if self.players == 0 {
+            return Err(SnlError::InvalidPlayers(self.players));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a single race
+#[derive(Debug)]
+struct RaceResult {
+    /// Seat of the first player to finish
+    winner: usize,
+    /// Round in which the winner finished
+    rounds: u64,
+    /// Number of times each seat was sent home by an opponent
+    bumped: Vec<u64>,
+}
+
+impl GameBoard {
+    /// Race `players` tokens from the start, each taking one turn per round in seat order
+    ///
+    /// Collisions are checked on the square where a turn ends, so squares passed during
+    /// bonus rolls do not bump anyone, and nobody is bumped from the start square.
+    fn simulate_race(&self, players: usize, collision: Collision) -> Result<RaceResult, SnlError> {
+        let mut rng = thread_rng();
+        let start_position = self.follow(0);
+        let mut positions = vec![start_position; players];
+        let mut bumped = vec![0; players];
+        let mut rounds = 0;
+
+        loop {
+            rounds += 1;
+            for seat in 0..players {
+                let position = self.take_turn(&mut rng, positions[seat])?;
+                positions[seat] = position;
+                if position == self.size {
+                    return Ok(RaceResult {
+                        winner: seat,
+                        rounds,
+                        bumped,
+                    });
+                }
+                if collision == Collision::SendHome && position != start_position {
+                    for other in (0..players).filter(|&other| other != seat) {
+                        if positions[other] == position {
+                            positions[other] = start_position;
+                            bumped[other] += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Exact probability of each seat winning, and the expected number of rounds, when
+    /// players do not interact
+    ///
+    /// Seat `i` wins in round `k` if it finishes on its `k`th turn, the seats before it
+    /// have not finished after `k` turns and the seats after it after `k - 1` turns. The
+    /// race lasts beyond round `k` only if no seat has finished after `k` turns.
+    fn exact_race(&self, players: usize) -> Result<(Vec<f64>, f64), SnlError> {
+        let start_position = self.follow(0);
+        let pmf = self.turn_distribution(start_position, usize::MAX, RACE_EPSILON)?;
+        let mut wins = vec![0.0; players];
+        let mut rounds = 0.0;
+        let mut survival_before: f64 = 1.0;
+
+        for &p in pmf.iter().skip(1) {
+            rounds += survival_before.powi(players as i32);
+            let survival = survival_before - p;
+            for (seat, win) in wins.iter_mut().enumerate() {
+                *win += p
+                    * survival.powi(seat as i32)
+                    * survival_before.powi((players - 1 - seat) as i32);
+            }
+            survival_before = survival;
+        }
+        Ok((wins, rounds))
+    }
+}
+
+/// Simulate multi-player races and report each seat's win probability
+pub fn snl_race(args: RaceArgs) -> Result<(), SnlError> {
+    args.validate()?;
+    let board = GameBoard::from_args(&args.board)?;
+    println!("Board: {}", board.name);
+    println!("Players: {}", args.players);
+
+    let start_time = Instant::now();
+    let results = (0..args.num_iterations)
+        .into_par_iter()
+        .map(|_| board.simulate_race(args.players, args.collision))
+        .collect::<Result<Vec<_>, _>>()?;
+    print_hms(&start_time);
+
+    let mut wins = vec![0u64; args.players];
+    let mut bumped = vec![0u64; args.players];
+    for result in &results {
+        wins[result.winner] += 1;
+        for (total, count) in bumped.iter_mut().zip(result.bumped.iter()) {
+            *total += count;
+        }
+    }
+    // Exact results assume independent players, so under collisions they are the
+    // baseline the simulated race is compared against
+    let (exact, exact_rounds) = board.exact_race(args.players)?;
+
+    let games = args.num_iterations as f64;
+    let mut file = File::create(RACE_FILENAME)?;
+    writeln!(file, "seat,wins,win_probability,exact_win_probability,bumps_per_game")?;
+    for seat in 0..args.players {
+        let fraction = wins[seat] as f64 / games;
+        let bumps = bumped[seat] as f64 / games;
+        match args.collision {
+            Collision::None => println!(
+                "Seat {}: win probability {} (exact {})",
+                seat + 1,
+                fraction,
+                exact[seat]
+            ),
+            Collision::SendHome => println!(
+                "Seat {}: win probability {} (without collisions {}, change {:+}), \
+                 bumped {} times per game",
+                seat + 1,
+                fraction,
+                exact[seat],
+                fraction - exact[seat],
+                bumps
+            ),
+        }
+        writeln!(
+            file,
+            "{},{},{},{},{}",
+            seat + 1,
+            wins[seat],
+            fraction,
+            exact[seat],
+            bumps
+        )?;
+    }
+    if args.collision != Collision::None {
+        let games_with_bumps = results
+            .iter()
+            .filter(|result| result.bumped.iter().any(|&count| count > 0))
+            .count();
+        println!("Bumps per game: {}", bumped.iter().sum::<u64>() as f64 / games);
+        println!("Games with a bump: {}", games_with_bumps as f64 / games);
+    }
+
+    println!("Rounds:");
+    let rounds = results.iter().map(|result| result.rounds).collect::<Vec<_>>();
+    SimulationStats::from_values(&rounds)?.print();
+    match args.collision {
+        Collision::None => println!("Exact expected rounds: {}", exact_rounds),
+        Collision::SendHome => {
+            let mean_rounds = rounds.iter().sum::<u64>() as f64 / games;
+            println!(
+                "Expected rounds without collisions: {} (change {:+})",
+                exact_rounds,
+                mean_rounds - exact_rounds
+            );
+        }
+    }
+
+    Ok(())
+}
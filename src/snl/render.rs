@@ -0,0 +1,422 @@
+use clap::{value_parser, Args, ValueEnum};
+use rand::thread_rng;
+use rayon::prelude::*;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use super::{BoardArgs, GameBoard, SnlError};
+use crate::stats::SimulationError;
+
+/// Characters shading a square in the ASCII rendering, from the lowest value to the highest
+const ASCII_SHADES: &[u8] = b" .:-=+*#%@";
+/// Side of one square in the SVG rendering, in pixels
+const SVG_CELL: usize = 60;
+/// Colour of the lowest and highest heatmap values in the SVG rendering
+const SVG_COLD: (f64, f64, f64) = (255.0, 255.0, 204.0);
+const SVG_HOT: (f64, f64, f64) = (189.0, 0.0, 38.0);
+
+/// Quantity each square can be coloured by
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Heatmap {
+    /// Expected turns left to finish from the square
+    ExpectedTurns,
+    /// Expected number of rolls landing on the square in a game from the start
+    Visits,
+    /// Probability that a game from the start lands on the snake starting on the square
+    SnakeHits,
+}
+
+impl Heatmap {
+    fn label(self) -> &'static str {
+        match self {
+            Heatmap::ExpectedTurns => "Expected turns left",
+            Heatmap::Visits => "Expected landings",
+            Heatmap::SnakeHits => "Snake hit probability",
+        }
+    }
+}
+
+/// Where heatmap values come from
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum HeatSource {
+    /// The exact Markov chain analysis
+    #[default]
+    Exact,
+    /// Simulated games
+    Simulation,
+}
+
+/// Command line arguments for drawing a board
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Also draw the board as SVG to this file
+    #[arg(long = "svg", required = false)]
+    svg: Option<PathBuf>,
+
+    /// Colour each square by this quantity
+    #[arg(long = "heatmap", required = false, value_enum)]
+    heatmap: Option<Heatmap>,
+
+    /// Compute heatmap values exactly or from simulated games
+    #[arg(long = "heat-source", value_enum, default_value_t = HeatSource::Exact)]
+    heat_source: HeatSource,
+
+    /// Number of games simulated for each heatmap value under --heat-source simulation
+    #[arg(short = 'n', required = false, value_parser = value_parser!(u64), default_value = "10000")]
+    num_iterations: u64,
+
+    /// Starting position for the visits and snake-hits heatmaps
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl RenderArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        if self.num_iterations == 0 {
+            return Err(SimulationError::InvalidIterationCount(self.num_iterations).into());
+        }
+        Ok(())
+    }
+}
+
+/// Value of a heatmap on each square, indexed by square, with `None` where it does not apply
+#[derive(Debug)]
+struct Heat {
+    heatmap: Heatmap,
+    values: Vec<Option<f64>>,
+    min: f64,
+    max: f64,
+}
+
+impl Heat {
+    fn new(heatmap: Heatmap, values: Vec<Option<f64>>) -> Self {
+        let known = || values.iter().flatten().copied();
+        let min = known().fold(f64::INFINITY, f64::min);
+        let max = known().fold(f64::NEG_INFINITY, f64::max);
+        Heat {
+            heatmap,
+            values,
+            min,
+            max,
+        }
+    }
+
+    /// Position of the value on `square` between the lowest and highest values, from 0 to 1
+    fn level(&self, square: usize) -> Option<f64> {
+        let value = self.values.get(square).copied().flatten()?;
+        let spread = self.max - self.min;
+        Some(if spread > 0.0 { (value - self.min) / spread } else { 0.0 })
+    }
+}
+
+impl GameBoard {
+    /// Expected turns left from each square, with 0 on the final square and the value
+    /// of where a snake or ladder leads on its start
+    fn heat_expected_turns(
+        &self,
+        source: HeatSource,
+        num_iterations: u64,
+    ) -> Result<Vec<Option<f64>>, SnlError> {
+        let mut rest = vec![0.0; self.size + 1];
+        match source {
+            HeatSource::Exact => {
+                let chain = self.absorbing_chain();
+                for (&square, &turns) in chain.squares.iter().zip(chain.expected_turns()?.iter()) {
+                    rest[square] = turns;
+                }
+            }
+            HeatSource::Simulation => {
+                let dice = self.dice_source();
+                for square in (0..self.size).filter(|s| !self.transitions.contains_key(s)) {
+                    let games = self.simulate_games(num_iterations, square, &dice)?;
+                    rest[square] = games.iter().map(|game| game.moves).sum::<u64>() as f64
+                        / num_iterations as f64;
+                }
+            }
+        }
+        Ok((0..=self.size).map(|square| Some(rest[self.follow(square)])).collect())
+    }
+
+    /// Expected landings on each square in a game from `start_position`, and the
+    /// probability of landing there at least once, estimated from simulated games
+    fn simulated_landings(
+        &self,
+        start_position: usize,
+        num_iterations: u64,
+    ) -> (Vec<f64>, Vec<f64>) {
+        let dice = self.dice_source();
+        let (landings, landed) = (0..num_iterations)
+            .into_par_iter()
+            .map(|_| {
+                let mut landings = vec![0u64; self.size + 1];
+                let mut source = dice.clone();
+                self.play_game(&mut thread_rng(), &mut source, start_position, |roll| {
+                    if roll.landed != roll.from {
+                        landings[roll.landed] += 1;
+                        for start in self.chained_starts(roll.landed) {
+                            landings[start] += 1;
+                        }
+                    }
+                });
+                let landed = landings.iter().map(|&count| u64::from(count > 0)).collect();
+                (landings, landed)
+            })
+            .reduce(
+                || (vec![0; self.size + 1], vec![0; self.size + 1]),
+                |(mut a, mut b), (c, d)| {
+                    a.iter_mut().zip(c).for_each(|(x, y)| *x += y);
+                    b.iter_mut().zip(d).for_each(|(x, y)| *x += y);
+                    (a, b)
+                },
+            );
+        let mean = |counts: Vec<u64>| {
+            counts.into_iter().map(|count| count as f64 / num_iterations as f64).collect()
+        };
+        (mean(landings), mean(landed))
+    }
+
+    /// Values of `heatmap` on every square
+    fn heat(
+        &self,
+        heatmap: Heatmap,
+        source: HeatSource,
+        start_position: usize,
+        num_iterations: u64,
+    ) -> Result<Heat, SnlError> {
+        if heatmap == Heatmap::ExpectedTurns {
+            return Ok(Heat::new(heatmap, self.heat_expected_turns(source, num_iterations)?));
+        }
+        let (landings, probability) = match source {
+            // A start that leads straight to the finish never lands on anything
+            _ if start_position == self.size => {
+                (vec![0.0; self.size + 1], vec![0.0; self.size + 1])
+            }
+            HeatSource::Exact => {
+                let mut landings = vec![0.0; self.size + 1];
+                let mut probability = vec![0.0; self.size + 1];
+                for visits in self.square_visits(start_position)? {
+                    landings[visits.square] = visits.landings;
+                    probability[visits.square] = visits.probability;
+                }
+                (landings, probability)
+            }
+            HeatSource::Simulation => self.simulated_landings(start_position, num_iterations),
+        };
+        let values = (0..=self.size)
+            .map(|square| match heatmap {
+                Heatmap::SnakeHits => self
+                    .transitions
+                    .get(&square)
+                    .filter(|&&to| to < square)
+                    .map(|_| probability[square]),
+                _ => (square > 0).then_some(landings[square]),
+            })
+            .collect();
+        Ok(Heat::new(heatmap, values))
+    }
+
+    /// Label of `square` with the snake (`v`) or ladder (`^`) starting there, e.g. `16v6`
+    fn square_label(&self, square: usize) -> String {
+        match self.transitions.get(&square) {
+            Some(&to) if to < square => format!("{}v{}", square, to),
+            Some(&to) => format!("{}^{}", square, to),
+            None => square.to_string(),
+        }
+    }
+
+    /// Draw the grid as text, top row first, shading each square by `heat` if given
+    fn render_ascii(&self, heat: Option<&Heat>) -> String {
+        let width = (1..=self.size)
+            .map(|square| self.square_label(square).len())
+            .max()
+            .unwrap_or(1)
+            + usize::from(heat.is_some());
+        let border = format!(
+            "+{}\n",
+            format!("{}+", "-".repeat(width + 2)).repeat(self.grid.columns)
+        );
+        let mut squares = vec![vec![0; self.grid.columns]; self.grid.rows];
+        for square in 1..=self.size {
+            if let Some((row, column)) = self.grid.cell(square) {
+                squares[row][column] = square;
+            }
+        }
+
+        let mut text = border.clone();
+        for row in squares.iter().rev() {
+            text += "|";
+            for &square in row {
+                let mut label = self.square_label(square);
+                if let Some(heat) = heat {
+                    let shade = heat.level(square).map_or(b' ', |level| {
+                        let last = ASCII_SHADES.len() - 1;
+                        ASCII_SHADES[(level * last as f64).round() as usize]
+                    });
+                    label.push(shade as char);
+                }
+                text += &format!(" {:>width$} |", label, width = width);
+            }
+            text += "\n";
+            text += &border;
+        }
+        text
+    }
+
+    /// Centre of `square` in the SVG drawing, or `None` for the off-board start
+    fn svg_centre(&self, square: usize) -> Option<(usize, usize)> {
+        let (row, column) = self.grid.cell(square)?;
+        Some((
+            column * SVG_CELL + SVG_CELL / 2,
+            (self.grid.rows - 1 - row) * SVG_CELL + SVG_CELL / 2,
+        ))
+    }
+
+    /// Draw the grid as SVG with snakes and ladders as arrows, colouring each square by
+    /// `heat` if given
+    fn render_svg<W: Write>(&self, writer: &mut W, heat: Option<&Heat>) -> Result<(), SnlError> {
+        let width = self.grid.columns * SVG_CELL;
+        let height = self.grid.rows * SVG_CELL;
+        let legend = if heat.is_some() { 30 } else { 0 };
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif">"#,
+            width,
+            height + legend
+        )?;
+        writeln!(writer, "<title>{}</title>", escape(&self.name))?;
+        writeln!(writer, "<defs>")?;
+        for (id, colour) in [("snake", "#c0392b"), ("ladder", "#27ae60")] {
+            writeln!(
+                writer,
+                r#"<marker id="{}" markerWidth="8" markerHeight="8" refX="6" refY="4" orient="auto"><path d="M0,0 L8,4 L0,8 z" fill="{}"/></marker>"#,
+                id, colour
+            )?;
+        }
+        writeln!(writer, "</defs>")?;
+
+        for square in 1..=self.size {
+            let Some((x, y)) = self.svg_centre(square) else {
+                continue;
+            };
+            let (left, top) = (x - SVG_CELL / 2, y - SVG_CELL / 2);
+            let fill = match heat {
+                Some(heat) => heat.level(square).map_or("#ffffff".to_string(), heat_colour),
+                None if ((left + top) / SVG_CELL).is_multiple_of(2) => "#f4ecd8".to_string(),
+                None => "#ffffff".to_string(),
+            };
+            writeln!(
+                writer,
+                r##"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="#999999"/>"##,
+                left, top, SVG_CELL, SVG_CELL, fill
+            )?;
+            writeln!(
+                writer,
+                r#"<text x="{}" y="{}" font-size="12">{}</text>"#,
+                left + 4,
+                top + 14,
+                square
+            )?;
+            if let Some(value) = heat.and_then(|heat| heat.values[square]) {
+                writeln!(
+                    writer,
+                    r#"<text x="{}" y="{}" font-size="10" text-anchor="end">{:.2}</text>"#,
+                    left + SVG_CELL - 4,
+                    top + SVG_CELL - 4,
+                    value
+                )?;
+            }
+        }
+
+        let mut features: Vec<(usize, usize)> =
+            self.transitions.iter().map(|(&from, &to)| (from, to)).collect();
+        features.sort_unstable();
+        for (from, to) in features {
+            let (Some((x1, y1)), Some((x2, y2))) = (self.svg_centre(from), self.svg_centre(to))
+            else {
+                continue;
+            };
+            let (id, colour) = if to < from {
+                ("snake", "#c0392b")
+            } else {
+                ("ladder", "#27ae60")
+            };
+            writeln!(
+                writer,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="3" marker-end="url(#{})"/>"#,
+                x1, y1, x2, y2, colour, id
+            )?;
+        }
+
+        if let Some(heat) = heat {
+            writeln!(
+                writer,
+                r#"<text x="4" y="{}" font-size="12">{}: {:.3} (light) to {:.3} (dark)</text>"#,
+                height + 20,
+                heat.heatmap.label(),
+                heat.min,
+                heat.max
+            )?;
+        }
+        writeln!(writer, "</svg>")?;
+        Ok(())
+    }
+}
+
+/// Fill colour for a heatmap level between 0 and 1
+fn heat_colour(level: f64) -> String {
+    let mix = |cold: f64, hot: f64| (cold + (hot - cold) * level).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(SVG_COLD.0, SVG_HOT.0),
+        mix(SVG_COLD.1, SVG_HOT.1),
+        mix(SVG_COLD.2, SVG_HOT.2)
+    )
+}
+
+/// Escape text for use inside SVG elements
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Draw the board in the terminal, and optionally as SVG, with an optional heatmap
+pub fn snl_render(args: RenderArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {} ({} grid)", board.name, board.grid);
+    let start_position = board.follow(args.start);
+
+    let heat = args
+        .heatmap
+        .map(|heatmap| board.heat(heatmap, args.heat_source, start_position, args.num_iterations))
+        .transpose()?;
+    print!("{}", board.render_ascii(heat.as_ref()));
+    if let Some(heat) = &heat {
+        let shades = String::from_utf8_lossy(ASCII_SHADES);
+        println!(
+            "{}: shades \"{}\" run from {:.3} to {:.3}",
+            heat.heatmap.label(),
+            shades,
+            heat.min,
+            heat.max
+        );
+    }
+
+    if let Some(path) = &args.svg {
+        let mut writer = BufWriter::new(File::create(path)?);
+        board.render_svg(&mut writer, heat.as_ref())?;
+        writer.flush()?;
+        println!("SVG written to {}", path.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use clap::{value_parser, Args};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{BoardArgs, GameBoard, SnlError};
+use crate::stats::SimulationError;
+use crate::util::print_hms;
+
+const SENSITIVITY_FILENAME: &str = "snl_sensitivity.csv";
+
+/// Command line arguments for the snake and ladder sensitivity analysis
+#[derive(Args)]
+pub struct SensitivityArgs {
+    /// Starting position on the board
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    /// Also move each end of every snake and ladder by up to this many squares
+    #[arg(long = "shift", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    shift: usize,
+
+    /// Estimate lengths from this many simulated games instead of the exact Markov chain
+    #[arg(long = "simulate", required = false, value_parser = value_parser!(u64))]
+    simulate: Option<u64>,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl SensitivityArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        if self.simulate == Some(0) {
+            return Err(SimulationError::InvalidIterationCount(0).into());
+        }
+        Ok(())
+    }
+}
+
+/// Expected length of the board with one snake or ladder removed or moved
+#[derive(Debug)]
+struct Variant {
+    /// The snake or ladder as it is on the original board
+    feature: (usize, usize),
+    /// What was changed, e.g. `removed` or `end -2`
+    change: String,
+    expected: f64,
+}
+
+impl Variant {
+    fn kind(&self) -> &'static str {
+        if self.feature.1 < self.feature.0 {
+            "snake"
+        } else {
+            "ladder"
+        }
+    }
+}
+
+impl GameBoard {
+    /// Expected length of this board's size and rules with different transitions,
+    /// which is infinite if they leave the finish unreachable
+    fn variant_length(
+        &self,
+        transitions: HashMap<usize, usize>,
+        start: usize,
+        simulate: Option<u64>,
+    ) -> Result<f64, SnlError> {
+        match GameBoard::new(self.name.clone(), self.grid, transitions, self.rules.clone()) {
+            Ok(board) => board.expected_length(start, simulate),
+            Err(SnlError::UnreachableFinish(_)) => Ok(f64::INFINITY),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Expected turns from `start`, exact unless a number of simulated games is given
+    ///
+    /// A board on which the finish cannot be reached has an infinite expected length.
+    fn expected_length(&self, start: usize, simulate: Option<u64>) -> Result<f64, SnlError> {
+        let start_position = self.follow(start);
+        if start_position == self.size {
+            return Ok(0.0);
+        }
+        match simulate {
+            Some(num_iterations) => {
+                let games =
+                    self.simulate_games(num_iterations, start_position, &self.dice_source())?;
+                Ok(games.iter().map(|game| game.moves).sum::<u64>() as f64 / num_iterations as f64)
+            }
+            None => match self.expected_turns(start_position) {
+                Err(SnlError::SingularMatrix) => Ok(f64::INFINITY),
+                result => result,
+            },
+        }
+    }
+
+    /// Move one end of the transition at `from` by `delta` squares, if the result is still
+    /// a snake or ladder of the same kind on the board that does not touch any other
+    fn shifted(
+        &self,
+        from: usize,
+        to: usize,
+        delta: isize,
+        move_end: bool,
+    ) -> Option<(usize, usize)> {
+        let moved = |square: usize| square.checked_add_signed(delta);
+        let (start, end) = if move_end {
+            (from, moved(to)?)
+        } else {
+            (moved(from)?, to)
+        };
+        let same_kind = (end < start) == (to < from) && end != start;
+        // Starting on another feature's end, or ending on another's start, would chain them
+        let others = self.transitions.iter().filter(|(&other, _)| other != from);
+        let free = others
+            .clone()
+            .all(|(&other_from, &other_to)| start != other_from && start != other_to)
+            && others.clone().all(|(&other_from, _)| end != other_from);
+        let on_board = start > 0 && start < self.size && end <= self.size;
+        (same_kind && free && on_board).then_some((start, end))
+    }
+
+    /// Expected length with each snake and ladder removed and, if `shift` is positive,
+    /// with each of its ends moved by up to `shift` squares either way
+    fn sensitivity(
+        &self,
+        start: usize,
+        shift: usize,
+        simulate: Option<u64>,
+    ) -> Result<Vec<Variant>, SnlError> {
+        let mut features: Vec<(usize, usize)> =
+            self.transitions.iter().map(|(&from, &to)| (from, to)).collect();
+        features.sort_unstable();
+        let mut variants = Vec::new();
+
+        for &(from, to) in &features {
+            let mut transitions = self.transitions.clone();
+            transitions.remove(&from);
+            variants.push(Variant {
+                feature: (from, to),
+                change: "removed".to_string(),
+                expected: self.variant_length(transitions, start, simulate)?,
+            });
+
+            for delta in (1..=shift as isize).flat_map(|d| [-d, d]) {
+                for (move_end, label) in [(false, "start"), (true, "end")] {
+                    if let Some((new_from, new_to)) = self.shifted(from, to, delta, move_end) {
+                        let mut transitions = self.transitions.clone();
+                        transitions.remove(&from);
+                        transitions.insert(new_from, new_to);
+                        variants.push(Variant {
+                            feature: (from, to),
+                            change: format!("{} {:+}", label, delta),
+                            expected: self.variant_length(transitions, start, simulate)?,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(variants)
+    }
+}
+
+/// Rank each snake and ladder by how much removing or moving it changes the expected length
+pub fn snl_sensitivity(args: SensitivityArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {}", board.name);
+
+    let start_time = Instant::now();
+    let baseline = board.expected_length(args.start, args.simulate)?;
+    let mut variants = board.sensitivity(args.start, args.shift, args.simulate)?;
+    print_hms(&start_time);
+
+    variants.sort_by(|a, b| {
+        (b.expected - baseline)
+            .abs()
+            .total_cmp(&(a.expected - baseline).abs())
+    });
+
+    println!("Expected turns: {}", baseline);
+    println!(
+        "{:>4}  {:<6} {:>9}  {:<9} {:>10} {:>10}",
+        "rank", "kind", "feature", "change", "expected", "delta"
+    );
+    let mut file = File::create(SENSITIVITY_FILENAME)?;
+    writeln!(file, "rank,kind,from,to,change,expected,delta")?;
+    for (rank, variant) in variants.iter().enumerate() {
+        let (from, to) = variant.feature;
+        let delta = variant.expected - baseline;
+        println!(
+            "{:>4}  {:<6} {:>9}  {:<9} {:>10.3} {:>+10.3}",
+            rank + 1,
+            variant.kind(),
+            format!("{}->{}", from, to),
+            variant.change,
+            variant.expected,
+            delta
+        );
+        writeln!(
+            file,
+            "{},{},{},{},{},{},{}",
+            rank + 1,
+            variant.kind(),
+            from,
+            to,
+            variant.change,
+            variant.expected,
+            delta
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use clap::{value_parser, Args};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{BoardArgs, GameBoard, SnlError};
+use crate::util::print_hms;
+
+const SHORTEST_FILENAME: &str = "snl_shortest.csv";
+
+/// Command line arguments for the exact shortest game report
+#[derive(Args)]
+pub struct ShortestArgs {
+    /// Starting position whose shortest game is printed
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl ShortestArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        Ok(())
+    }
+}
+
+/// The fastest games from one square
+#[derive(Debug)]
+struct ShortestGame {
+    square: usize,
+    /// Fewest turns in which the game can finish
+    turns: usize,
+    /// Number of sequences of roll totals finishing in that many turns, saturating at `u128::MAX`
+    sequences: u128,
+    /// Exact probability of finishing in that many turns
+    probability: f64,
+}
+
+impl GameBoard {
+    /// Number of sequences of roll totals in one turn from `square` ending on each square
+    /// it can reach
+    ///
+    /// This is the turn resolution run with every possible total weighted one instead of
+    /// by its probability, so bonus rolls and penalties are counted the same way.
+    fn turn_sequences(&self, square: usize) -> Vec<(usize, u128)> {
+        let rolls: Vec<f64> = self
+            .rules
+            .dice
+            .distribution()
+            .iter()
+            .map(|&p| if p > 0.0 { 1.0 } else { 0.0 })
+            .collect();
+        self.resolve_turn(square, &rolls, None)
+            .outcomes
+            .into_iter()
+            .map(|(to, count)| (to, count.round() as u128))
+            .collect()
+    }
+
+    /// Shortest game from every square a token can rest on, by breadth-first search
+    /// backwards from the final square over the turns with nonzero probability
+    ///
+    /// Squares are settled in order of distance, so the sequences and probability of
+    /// each come from its successors one turn closer to the finish.
+    fn shortest_games(&self) -> Vec<ShortestGame> {
+        let predecessors = self.transition_matrix.transpose();
+
+        let mut distance = vec![usize::MAX; self.size + 1];
+        let mut sequences = vec![0u128; self.size + 1];
+        let mut probability = vec![0.0; self.size + 1];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([self.size]);
+        distance[self.size] = 0;
+        sequences[self.size] = 1;
+        probability[self.size] = 1.0;
+        while let Some(square) = queue.pop_front() {
+            for (previous, _) in predecessors.row(square) {
+                if distance[previous] == usize::MAX {
+                    distance[previous] = distance[square] + 1;
+                    order.push(previous);
+                    queue.push_back(previous);
+                }
+            }
+        }
+
+        for &square in &order {
+            for (to, count) in self.turn_sequences(square) {
+                if distance[to] + 1 == distance[square] {
+                    sequences[square] =
+                        sequences[square].saturating_add(count.saturating_mul(sequences[to]));
+                    probability[square] += self.transition_matrix.get(square, to) * probability[to];
+                }
+            }
+        }
+
+        let mut games: Vec<ShortestGame> = order
+            .into_iter()
+            .map(|square| ShortestGame {
+                square,
+                turns: distance[square],
+                sequences: sequences[square],
+                probability: probability[square],
+            })
+            .collect();
+        games.sort_unstable_by_key(|game| game.square);
+        games
+    }
+}
+
+/// Report the exact shortest game, how many roll sequences achieve it and its probability
+/// from every square
+pub fn snl_shortest(args: ShortestArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {}", board.name);
+    let start_position = board.follow(args.start);
+
+    let start_time = Instant::now();
+    let games = board.shortest_games();
+    print_hms(&start_time);
+
+    let mut file = File::create(SHORTEST_FILENAME)?;
+    writeln!(file, "square,min_turns,roll_sequences,probability")?;
+    for game in &games {
+        writeln!(
+            file,
+            "{},{},{},{}",
+            game.square, game.turns, game.sequences, game.probability
+        )?;
+    }
+
+    match games.iter().find(|game| game.square == start_position) {
+        Some(game) => {
+            println!("Shortest Path Length: {}", game.turns);
+            println!("Roll sequences: {}", game.sequences);
+            println!("Shortest Path Fraction: {}", game.probability);
+        }
+        None => println!("Shortest Path Length: 0"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use super::SnlError;
+
+/// Diagonal entries of I - A smaller than this are treated as zero when solving
+const PIVOT_TOLERANCE: f64 = 1e-12;
+/// Solving stops once a sweep changes no entry by more than this fraction of the largest
+const SOLVE_TOLERANCE: f64 = 1e-14;
+/// Nor until no equation is off by more than this fraction of the largest entry
+const RESIDUAL_TOLERANCE: f64 = 1e-12;
+/// Give up on a system that has not converged after this many sweeps
+const MAX_SWEEPS: usize = 1_000_000;
+
+/// Square matrix in compressed sparse row (CSR) form
+///
+/// Each turn can only end on the few squares a roll reaches, so the transition matrix
+/// of a board has a handful of nonzero entries per row. Storing only those keeps memory
+/// and every product and solve linear in the number of squares rather than quadratic.
+#[derive(Clone, Debug, Default)]
+pub(super) struct SparseMatrix {
+    /// Offset of each row's first entry in `columns` and `values`, plus the total at the end
+    row_starts: Vec<usize>,
+    /// Column of each entry, increasing within each row
+    columns: Vec<usize>,
+    values: Vec<f64>,
+}
+
+impl SparseMatrix {
+    /// Matrix with the given rows of `(column, value)` entries, dropping any zeros
+    pub(super) fn from_rows<I, R>(rows: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoIterator<Item = (usize, f64)>,
+    {
+        let mut matrix = SparseMatrix {
+            row_starts: vec![0],
+            ..SparseMatrix::default()
+        };
+        for row in rows {
+            let mut entries: Vec<(usize, f64)> =
+                row.into_iter().filter(|&(_, value)| value != 0.0).collect();
+            entries.sort_unstable_by_key(|&(column, _)| column);
+            for (column, value) in entries {
+                matrix.columns.push(column);
+                matrix.values.push(value);
+            }
+            matrix.row_starts.push(matrix.columns.len());
+        }
+        matrix
+    }
+
+    /// Number of rows, which is also the number of columns
+    pub(super) fn len(&self) -> usize {
+        self.row_starts.len() - 1
+    }
+
+    /// Nonzero entries of row `i` as `(column, value)`, in increasing column order
+    pub(super) fn row(&self, i: usize) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let range = self.row_starts[i]..self.row_starts[i + 1];
+        self.columns[range.clone()]
+            .iter()
+            .copied()
+            .zip(self.values[range].iter().copied())
+    }
+
+    /// Entry in row `i` and column `j`
+    pub(super) fn get(&self, i: usize, j: usize) -> f64 {
+        let range = self.row_starts[i]..self.row_starts[i + 1];
+        match self.columns[range.clone()].binary_search(&j) {
+            Ok(offset) => self.values[range.start + offset],
+            Err(_) => 0.0,
+        }
+    }
+
+    /// Transposed matrix, whose row `j` lists the entries of column `j`
+    pub(super) fn transpose(&self) -> Self {
+        let mut rows = vec![Vec::new(); self.len()];
+        for i in 0..self.len() {
+            for (j, value) in self.row(i) {
+                rows[j].push((i, value));
+            }
+        }
+        SparseMatrix::from_rows(rows)
+    }
+
+    /// Product of the matrix with a column vector
+    pub(super) fn multiply(&self, vector: &[f64]) -> Vec<f64> {
+        (0..self.len())
+            .map(|i| self.row(i).map(|(j, value)| value * vector[j]).sum())
+            .collect()
+    }
+
+    /// Solve `(I - A) x = rhs`, where `A` is this matrix, by Gauss-Seidel iteration
+    ///
+    /// For the transient block of an absorbing chain, I - A is a nonsingular M-matrix,
+    /// so the iteration converges from any start. Sweeps alternate between increasing
+    /// and decreasing order, so information travels quickly both along the direction
+    /// tokens move and back down snakes. A small change between sweeps can hide slow
+    /// progress, so the residual of the equations is checked before accepting the answer.
+    pub(super) fn solve_absorbing(&self, rhs: &[f64]) -> Result<Vec<f64>, SnlError> {
+        let n = self.len();
+        let diagonal: Vec<f64> = (0..n).map(|i| 1.0 - self.get(i, i)).collect();
+        if diagonal.iter().any(|&d| d.abs() < PIVOT_TOLERANCE) {
+            return Err(SnlError::SingularMatrix);
+        }
+
+        let mut x = vec![0.0; n];
+        for sweep in 0..MAX_SWEEPS {
+            let mut change: f64 = 0.0;
+            let mut update = |i: usize, x: &mut Vec<f64>| {
+                let off_diagonal: f64 =
+                    self.row(i).filter(|&(j, _)| j != i).map(|(j, value)| value * x[j]).sum();
+                let value = (rhs[i] + off_diagonal) / diagonal[i];
+                change = change.max((value - x[i]).abs());
+                x[i] = value;
+            };
+            if sweep % 2 == 0 {
+                (0..n).for_each(|i| update(i, &mut x));
+            } else {
+                (0..n).rev().for_each(|i| update(i, &mut x));
+            }
+
+            let largest = x.iter().fold(0.0, |m: f64, v| m.max(v.abs()));
+            if !largest.is_finite() {
+                return Err(SnlError::SingularMatrix);
+            }
+            if change <= SOLVE_TOLERANCE * largest
+                && self.residual(&x, rhs) <= RESIDUAL_TOLERANCE * largest.max(1.0)
+            {
+                return Ok(x);
+            }
+        }
+        Err(SnlError::NoConvergence(MAX_SWEEPS))
+    }
+
+    /// Largest error in any equation of `(I - A) x = rhs`
+    fn residual(&self, x: &[f64], rhs: &[f64]) -> f64 {
+        let product = self.multiply(x);
+        (0..self.len()).fold(0.0, |m: f64, i| m.max((x[i] - product[i] - rhs[i]).abs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_absorbing_satisfies_every_equation() {
+        // A walk on three squares that moves on or falls back to the first
+        let matrix = SparseMatrix::from_rows([
+            vec![(1, 0.5)],
+            vec![(0, 0.25), (2, 0.5)],
+            vec![(0, 0.5), (1, 0.25)],
+        ]);
+        let rhs = [1.0, 1.0, 1.0];
+        let x = matrix.solve_absorbing(&rhs).unwrap();
+        assert!(matrix.residual(&x, &rhs) < 1e-12);
+        // Solved by hand: x0 = 1 + x1 / 2, x1 = 1 + x0 / 4 + x2 / 2, x2 = 1 + x0 / 2 + x1 / 4
+        let expected = [2.6, 3.2, 3.1];
+        for (value, expected) in x.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12, "{} != {}", value, expected);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use clap::{value_parser, Args};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    fs::File,
+    io::Write,
+    time::Instant,
+};
+
+use super::{exact::AbsorbingChain, sparse::SparseMatrix, BoardArgs, GameBoard, SnlError};
+use crate::util::print_hms;
+
+const SPECTRAL_FILENAME: &str = "snl_spectral.csv";
+/// Iterations of the deflated matrix used to estimate the second largest eigenvalue,
+/// the second half of which are averaged
+const SUBDOMINANT_ITERATIONS: usize = 2000;
+
+/// Command line arguments for the spectral analysis of the game chain
+#[derive(Args)]
+pub struct SpectralArgs {
+    /// Starting position on the board
+    #[arg(long = "start", required = false, value_parser = value_parser!(usize), default_value = "0")]
+    start: usize,
+
+    /// Report the number of turns beyond which fewer than this fraction of games last
+    #[arg(long = "threshold", required = false, value_parser = value_parser!(f64), default_value = "1e-6")]
+    threshold: f64,
+
+    /// Stop power iteration once no entry of the eigenvector changes by more than this
+    #[arg(long = "tolerance", required = false, value_parser = value_parser!(f64), default_value = "1e-12")]
+    tolerance: f64,
+
+    /// Give up if power iteration has not converged after this many steps
+    #[arg(long = "max-iterations", required = false, value_parser = value_parser!(usize), default_value = "1000000")]
+    max_iterations: usize,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl SpectralArgs {
+    /// Validate command line arguments against the board in play
+    fn validate(&self, board: &GameBoard) -> Result<(), SnlError> {
+        if self.start >= board.size {
+            return Err(SnlError::InvalidStart(self.start));
+        }
+        if !(self.threshold > 0.0 && self.threshold < 1.0) {
+            return Err(SnlError::InvalidThreshold(self.threshold));
+        }
+        Ok(())
+    }
+}
+
+/// Dominant eigenvalue of the transient block Q and its eigenvectors
+///
+/// Q is non-negative, so by Perron-Frobenius its largest eigenvalue is real and equal to
+/// its spectral radius, with non-negative eigenvectors. A Q that is nilpotent, as on a
+/// board where tokens only move forward, has a dominant eigenvalue of zero.
+#[derive(Debug)]
+struct Spectrum {
+    /// Largest eigenvalue, the rate at which the tail of the game length decays
+    value: f64,
+    /// Right eigenvector, Q r = value r, scaled to a largest entry of 1
+    right: Vec<f64>,
+    /// Left eigenvector, l Q = value l, scaled to a largest entry of 1
+    left: Vec<f64>,
+    /// Steps of power iteration taken for the right eigenvector
+    iterations: usize,
+    /// Estimated modulus of the second largest eigenvalue
+    subdominant: f64,
+}
+
+impl Spectrum {
+    /// Constant C in P(T > k) ~ C value^k for a game from transient state `i`
+    ///
+    /// P(T > k) = e_i Q^k 1, and Q^k approaches value^k r l / (l r) as k grows.
+    fn tail_constant(&self, i: usize) -> f64 {
+        let overlap: f64 = self.left.iter().zip(self.right.iter()).map(|(l, r)| l * r).sum();
+        if overlap > 0.0 {
+            self.right[i] * self.left.iter().sum::<f64>() / overlap
+        } else {
+            0.0
+        }
+    }
+
+    /// Smallest k with C value^k below `threshold`, or `None` if the tail vanishes
+    fn cutoff(&self, constant: f64, threshold: f64) -> Option<u64> {
+        if self.value <= 0.0 || constant <= 0.0 {
+            return None;
+        }
+        if constant < threshold {
+            return Some(0);
+        }
+        Some(((threshold / constant).ln() / self.value.ln()).floor() as u64 + 1)
+    }
+}
+
+/// Largest eigenvalue of a non-negative matrix and its eigenvector, by power iteration
+/// from the all-ones vector
+///
+/// The vector is rescaled to a largest entry of 1 each step, so the scale factor
+/// converges to the eigenvalue. If the vector reaches zero the matrix is nilpotent
+/// and the eigenvalue is zero.
+fn power_iteration(
+    matrix: &SparseMatrix,
+    tolerance: f64,
+    max_iterations: usize,
+) -> Result<(f64, Vec<f64>, usize), SnlError> {
+    let mut vector = vec![1.0; matrix.len()];
+    for iteration in 1..=max_iterations {
+        let mut next = matrix.multiply(&vector);
+        let scale = next.iter().fold(0.0, |m: f64, x| m.max(x.abs()));
+        if scale == 0.0 {
+            return Ok((0.0, vector, iteration));
+        }
+        next.iter_mut().for_each(|x| *x /= scale);
+        let change = next
+            .iter()
+            .zip(vector.iter())
+            .fold(0.0, |m: f64, (a, b)| m.max((a - b).abs()));
+        vector = next;
+        if change <= tolerance {
+            return Ok((scale, vector, iteration));
+        }
+    }
+    Err(SnlError::NoConvergence(max_iterations))
+}
+
+impl AbsorbingChain {
+    /// Dominant eigenvalue of Q with its right and left eigenvectors, and an estimate of
+    /// the modulus of the next largest eigenvalue
+    ///
+    /// The estimate iterates Q with the dominant part r l / (l r) removed from a random
+    /// start, and averages the growth rate over the last half of the steps. It may be
+    /// rough when several eigenvalues are close, but it tells how soon the tail settles
+    /// into its geometric decay.
+    fn spectrum(&self, tolerance: f64, max_iterations: usize) -> Result<Spectrum, SnlError> {
+        let (value, right, iterations) = power_iteration(&self.q, tolerance, max_iterations)?;
+        let (_, left, _) = power_iteration(&self.q.transpose(), tolerance, max_iterations)?;
+
+        let overlap: f64 = left.iter().zip(right.iter()).map(|(l, r)| l * r).sum();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut vector: Vec<f64> =
+            (0..self.squares.len()).map(|_| rng.gen::<f64>() - 0.5).collect();
+        let mut log_growth = 0.0;
+        let mut subdominant = 0.0;
+        for step in 0..SUBDOMINANT_ITERATIONS {
+            let projection = if overlap > 0.0 {
+                left.iter().zip(vector.iter()).map(|(l, v)| l * v).sum::<f64>() / overlap
+            } else {
+                0.0
+            };
+            let mut next = self.q.multiply(&vector);
+            next.iter_mut().zip(right.iter()).for_each(|(x, r)| *x -= value * projection * r);
+            let norm = next.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm == 0.0 {
+                break;
+            }
+            next.iter_mut().for_each(|x| *x /= norm);
+            if step >= SUBDOMINANT_ITERATIONS / 2 {
+                log_growth += norm.ln();
+                subdominant = (log_growth / (step + 1 - SUBDOMINANT_ITERATIONS / 2) as f64).exp();
+            }
+            vector = next;
+        }
+
+        Ok(Spectrum {
+            value,
+            right,
+            left,
+            iterations,
+            subdominant,
+        })
+    }
+}
+
+/// Report the tail decay rate of the game length and where the longest games end
+pub fn snl_spectral(args: SpectralArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    args.validate(&board)?;
+    println!("Board: {}", board.name);
+    let start_position = board.follow(args.start);
+    if start_position == board.size {
+        println!("Every game from square {} finishes at once", args.start);
+        return Ok(());
+    }
+
+    let start_time = Instant::now();
+    let chain = board.absorbing_chain();
+    let spectrum = chain.spectrum(args.tolerance, args.max_iterations)?;
+    let pmf = board.turn_distribution(start_position, usize::MAX, args.threshold)?;
+    print_hms(&start_time);
+
+    let index = chain
+        .position(start_position)
+        .ok_or(SnlError::InvalidPosition(start_position))?;
+    let constant = spectrum.tail_constant(index);
+    println!(
+        "Dominant eigenvalue: {} (power iteration converged after {} steps)",
+        spectrum.value, spectrum.iterations
+    );
+    println!("Second largest eigenvalue modulus (estimate): {}", spectrum.subdominant);
+    if spectrum.value > 0.0 {
+        println!("Tail: P(T > k) ~ {} * {}^k", constant, spectrum.value);
+        println!("Half-life of the tail: {} turns", -(2f64.ln()) / spectrum.value.ln());
+    } else {
+        println!("Tail: games have a longest possible length, so the tail ends");
+    }
+    if let Some(cutoff) = spectrum.cutoff(constant, args.threshold) {
+        println!(
+            "Fewer than {} of games last more than {} turns (asymptotic)",
+            args.threshold, cutoff
+        );
+    }
+    println!(
+        "Fewer than {} of games last more than {} turns (exact)",
+        args.threshold,
+        pmf.len() - 1
+    );
+
+    let mut file = File::create(SPECTRAL_FILENAME)?;
+    writeln!(file, "turn,tail,asymptotic_tail")?;
+    let mut tail = 1.0;
+    for (turn, p) in pmf.iter().enumerate() {
+        tail -= p;
+        writeln!(
+            file,
+            "{},{},{}",
+            turn,
+            tail,
+            constant * spectrum.value.powi(turn as i32)
+        )?;
+    }
+
+    Ok(())
+}
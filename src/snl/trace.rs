@@ -0,0 +1,236 @@
+use clap::{value_parser, Args};
+use rand::thread_rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+};
+
+use super::{
+    BoardArgs, GameBoard, GameMetrics, MoveSource, MovementArgs, RollAgain, SnlError,
+    ROLL_AGAIN_LIMIT,
+};
+
+/// One roll of a traced game
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct TraceRoll {
+    /// Turn the roll belongs to, counting from 1
+    pub(super) turn: u64,
+    /// Total rolled
+    pub(super) roll: usize,
+    /// Square the token was on before the roll
+    pub(super) from: usize,
+    /// Square the roll moved the token to, before any snake or ladder
+    pub(super) landed: usize,
+    /// Square the token ended on, after any snake or ladder or penalty
+    pub(super) to: usize,
+    /// Whether this roll completed a run of highest rolls and drew the roll-again penalty
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(super) penalty: bool,
+}
+
+/// Every roll of one game, written as a single JSON line
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct GameTrace {
+    /// Square the game started on, after any snake or ladder there
+    start: usize,
+    /// Number of turns taken to finish
+    turns: u64,
+    rolls: Vec<TraceRoll>,
+}
+
+/// Command line arguments for replaying a trace file
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Trace file written by `--trace`, one game per line
+    trace: PathBuf,
+
+    /// Print every roll of this game, counting lines from 1
+    #[arg(long = "game", required = false, value_parser = value_parser!(usize))]
+    game: Option<usize>,
+
+    #[command(flatten)]
+    movement: MovementArgs,
+
+    #[command(flatten)]
+    board: BoardArgs,
+}
+
+impl GameBoard {
+    /// Play one game from `start_position` with moves from `source`, recording every roll
+    fn play_traced_game(&self, start_position: usize, mut source: MoveSource) -> GameTrace {
+        let mut rolls = Vec::new();
+        let turns = self.play_game(&mut thread_rng(), &mut source, start_position, |roll| {
+            rolls.push(roll)
+        });
+        GameTrace {
+            start: start_position,
+            turns,
+            rolls,
+        }
+    }
+
+    /// Simulate games with full traces, write them as JSON Lines and return each game's metrics
+    pub(super) fn simulate_traced_games<W: Write>(
+        &self,
+        num_iterations: u64,
+        start_position: usize,
+        source: &MoveSource,
+        writer: &mut W,
+    ) -> Result<Vec<GameMetrics>, SnlError> {
+        if start_position >= self.size {
+            return Err(SnlError::InvalidPosition(start_position));
+        }
+
+        let traces: Vec<GameTrace> = (0..num_iterations)
+            .into_par_iter()
+            .map(|_| self.play_traced_game(start_position, source.clone()))
+            .collect();
+
+        let mut games = Vec::with_capacity(traces.len());
+        for trace in &traces {
+            serde_json::to_writer(&mut *writer, trace).map_err(std::io::Error::from)?;
+            writeln!(writer)?;
+            let mut metrics = GameMetrics {
+                moves: trace.turns,
+                ..GameMetrics::default()
+            };
+            trace.rolls.iter().for_each(|roll| metrics.record(roll, self));
+            games.push(metrics);
+        }
+        Ok(games)
+    }
+
+    /// Check that a traced game is possible on this board under its rules, with moves
+    /// that `source` can give
+    ///
+    /// A deck is only checked for the values of its cards, not the order they can be drawn in.
+    fn check_trace(&self, trace: &GameTrace, source: &MoveSource) -> Result<(), (usize, String)> {
+        let highest = source.highest();
+        let mut position = trace.start;
+        let mut turn = 0;
+        let mut turn_start = position;
+        let mut streak = 0;
+        let mut turn_over = true;
+
+        for (i, roll) in trace.rolls.iter().enumerate() {
+            let fail = |reason: String| Err((i + 1, reason));
+            if position == self.size {
+                return fail("roll after the game finished".to_string());
+            }
+            if turn_over {
+                turn += 1;
+                turn_start = position;
+                streak = 0;
+            }
+            if roll.turn != turn {
+                return fail(format!("expected turn {}, found {}", turn, roll.turn));
+            }
+            if roll.from != position {
+                return fail(format!("token was on {}, not {}", position, roll.from));
+            }
+            if !source.allows(roll.roll) {
+                return fail(format!("{} cannot be rolled", roll.roll));
+            }
+
+            let bonus = roll.roll == highest && self.rules.roll_again != RollAgain::Off;
+            let penalty = bonus && streak + 1 == ROLL_AGAIN_LIMIT;
+            let landed = if penalty {
+                position
+            } else {
+                self.rules.overshoot.apply(position, position + roll.roll, self.size)
+            };
+            let to = match (penalty, self.rules.roll_again) {
+                (true, RollAgain::Restart) => 0,
+                (true, _) => turn_start,
+                _ => self.follow(landed),
+            };
+            let expected = TraceRoll {
+                turn,
+                roll: roll.roll,
+                from: position,
+                landed,
+                to,
+                penalty,
+            };
+            if *roll != expected {
+                return fail(format!("expected {:?}, found {:?}", expected, roll));
+            }
+
+            position = to;
+            streak += 1;
+            turn_over = !bonus || penalty || position == self.size;
+        }
+
+        let last = trace.rolls.len();
+        if position != self.size {
+            return Err((last, format!("game ended on {}, not the final square", position)));
+        }
+        if trace.turns != turn {
+            return Err((last, format!("recorded {} turns but played {}", trace.turns, turn)));
+        }
+        Ok(())
+    }
+}
+
+impl TraceRoll {
+    /// Describe the roll for stepping through a game, with each snake and ladder of a chain
+    fn describe(&self, board: &GameBoard) -> String {
+        let mut text = format!("Turn {}: rolled {} from {}", self.turn, self.roll, self.from);
+        if self.penalty {
+            text += &format!(", penalty back to {}", self.to);
+        } else {
+            text += &format!(", landed on {}", self.landed);
+            if board.transitions.contains_key(&self.landed) {
+                let hops = std::iter::once(self.landed).chain(board.chained_starts(self.landed));
+                for from in hops {
+                    match board.transitions[&from] {
+                        to if to < from => text += &format!(", snake down to {}", to),
+                        to => text += &format!(", ladder up to {}", to),
+                    }
+                }
+            }
+        }
+        text
+    }
+}
+
+/// Re-check every game in a trace file against the board, optionally printing one of them
+pub fn snl_replay(args: ReplayArgs) -> Result<(), SnlError> {
+    let board = GameBoard::from_args(&args.board)?;
+    let source = args.movement.source(&board)?;
+    println!("Board: {}", board.name);
+    let contents = fs::read_to_string(&args.trace)?;
+    let mut games = 0;
+    let mut rolls = 0;
+
+    for (i, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let trace: GameTrace = serde_json::from_str(line).map_err(|e| SnlError::TraceParse {
+            line: i + 1,
+            message: e.to_string(),
+        })?;
+        if args.game == Some(i + 1) {
+            println!("Game {} from square {}:", i + 1, trace.start);
+            for roll in &trace.rolls {
+                println!("{}", roll.describe(&board));
+            }
+            println!("Finished in {} turns", trace.turns);
+        }
+        board
+            .check_trace(&trace, &source)
+            .map_err(|(roll, reason)| SnlError::ReplayMismatch {
+                line: i + 1,
+                roll,
+                reason,
+            })?;
+        games += 1;
+        rolls += trace.rolls.len();
+    }
+
+    println!("Replayed {} games and {} rolls with no mismatches", games, rolls);
+    Ok(())
+}
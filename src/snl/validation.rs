@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use super::{Chaining, GameBoard, SnlError};
+
+impl GameBoard {
+    /// Check that every snake and ladder starts and ends on the board, none starts on
+    /// the off-board start square 0 or the final square, and they never form a cycle.
+    /// Without chaining, none may end where another starts either.
+    pub(super) fn validate_transitions(
+        size: usize,
+        transitions: &HashMap<usize, usize>,
+        chaining: Chaining,
+    ) -> Result<(), SnlError> {
+        let mut entries: Vec<(usize, usize)> =
+            transitions.iter().map(|(&from, &to)| (from, to)).collect();
+        entries.sort_unstable();
+
+        for &(from, to) in &entries {
+            if from > size || to > size {
+                return Err(SnlError::TransitionOffBoard { from, to, size });
+            }
+            if from == 0 {
+                return Err(SnlError::TransitionFromStart(to));
+            }
+            if from == size {
+                return Err(SnlError::TransitionFromFinal(from));
+            }
+        }
+        // Cycles are reported before chains, as every cycle is also a chain
+        for &(from, _) in &entries {
+            let mut path = vec![from];
+            let mut square = from;
+            while let Some(&next) = transitions.get(&square) {
+                if let Some(i) = path.iter().position(|&seen| seen == next) {
+                    return Err(SnlError::TransitionCycle(path[i..].to_vec()));
+                }
+                path.push(next);
+                square = next;
+            }
+        }
+        for &(from, to) in entries.iter().filter(|_| chaining == Chaining::Single) {
+            if let Some(&next) = transitions.get(&to) {
+                return Err(SnlError::ChainedTransition { from, to, next });
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the final square can be reached from every square a token can rest on
+    pub(super) fn validate_reachable(&self) -> Result<(), SnlError> {
+        let mut reaches_finish = vec![false; self.size + 1];
+        reaches_finish[self.size] = true;
+        let mut frontier = vec![self.size];
+        let predecessors = self.transition_matrix.transpose();
+
+        while let Some(square) = frontier.pop() {
+            for (from, _) in predecessors.row(square) {
+                if !reaches_finish[from] {
+                    reaches_finish[from] = true;
+                    frontier.push(from);
+                }
+            }
+        }
+
+        match (0..self.size)
+            .find(|square| !reaches_finish[*square] && !self.transitions.contains_key(square))
+        {
+            Some(square) => Err(SnlError::UnreachableFinish(square)),
+            None => Ok(()),
+        }
+    }
+}
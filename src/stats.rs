@@ -20,14 +20,16 @@ pub enum SimulationError {
 #[derive(Debug)]
 pub struct SimulationStats {
     min_moves: u64,
-    max_moves: u64,
+    /// Longest observed run; `None` for exact statistics, where it is unbounded
+    max_moves: Option<u64>,
     min_moves_fraction: f64,
     mean: f64,
     variance: f64,
     std_deviation: f64,
     coeff_variation: f64,
-    skewness: f64,
-    kurtosis: f64,
+    /// Skewness and kurtosis; `None` when every run has the same length, leaving them undefined
+    skewness: Option<f64>,
+    kurtosis: Option<f64>,
     median: f64,
 }
 
@@ -56,32 +58,159 @@ impl SimulationStats {
 
         Ok(SimulationStats {
             min_moves,
-            max_moves,
+            max_moves: Some(max_moves),
             min_moves_fraction: (min_moves_count as f64) / count,
             mean,
             variance,
             std_deviation,
             coeff_variation: std_deviation / mean,
-            skewness: calculate_skewness(values, mean, std_deviation)?,
-            kurtosis: calculate_kurtosis(values, mean, std_deviation)?,
+            skewness: Some(calculate_skewness(values, mean, std_deviation)?),
+            kurtosis: Some(calculate_kurtosis(values, mean, std_deviation)?),
+            median,
+        })
+    }
+
+    /// Calculate statistical metrics from a probability mass function, where
+    /// `pmf[k]` is the probability that a run takes exactly `k` moves
+    ///
+    /// The distribution is normalised by its total mass, so a truncated tail gives
+    /// statistics conditional on finishing within `pmf.len() - 1` moves.
+    pub(crate) fn from_distribution(pmf: &[f64]) -> Result<Self, SimulationError> {
+        let total: f64 = pmf.iter().sum();
+        if total <= 0.0 {
+            return Err(SimulationError::EmptyMoveArray);
+        }
+
+        let min_moves = pmf.iter().position(|&p| p > 0.0).unwrap(); // Safe because total is positive
+        let max_moves = pmf.iter().rposition(|&p| p > 0.0).unwrap();
+        let mean = pmf.iter().enumerate().map(|(k, &p)| k as f64 * p).sum::<f64>() / total;
+        let central_moment = |power: i32| {
+            pmf.iter()
+                .enumerate()
+                .map(|(k, &p)| p * (k as f64 - mean).powi(power))
+                .sum::<f64>()
+                / total
+        };
+        let variance = central_moment(2);
+        let std_deviation = variance.sqrt();
+
+        if std_deviation == 0.0 {
+            return Err(SimulationError::StatisticalError(
+                "Standard deviation is zero".to_string(),
+            ));
+        }
+        let median = distribution_quantile(pmf, 0.5 * total).unwrap_or(max_moves as u64) as f64;
+
+        Ok(SimulationStats {
+            min_moves: min_moves as u64,
+            max_moves: Some(max_moves as u64),
+            min_moves_fraction: pmf[min_moves] / total,
+            mean,
+            variance,
+            std_deviation,
+            coeff_variation: std_deviation / mean,
+            skewness: Some(central_moment(3) / std_deviation.powi(3)),
+            kurtosis: Some(central_moment(4) / std_deviation.powi(4)),
+            median,
+        })
+    }
+
+    /// Calculate exact statistical metrics from the first four factorial moments
+    /// E[T], E[T(T-1)], E[T(T-1)(T-2)] and E[T(T-1)(T-2)(T-3)] of the number of moves
+    ///
+    /// The shortest run, its probability and the median cannot be recovered from the
+    /// moments, so they are passed in directly. There is no observed maximum.
+    pub(crate) fn from_factorial_moments(
+        min_moves: u64,
+        min_moves_fraction: f64,
+        factorial_moments: &[f64; 4],
+        median: f64,
+    ) -> Result<Self, SimulationError> {
+        let [f1, f2, f3, f4] = *factorial_moments;
+        // Raw moments via Stirling numbers of the second kind
+        let m1 = f1;
+        let m2 = f2 + f1;
+        let m3 = f3 + 3.0 * f2 + f1;
+        let m4 = f4 + 6.0 * f3 + 7.0 * f2 + f1;
+
+        let mean = m1;
+        let variance = m2 - mean.powi(2);
+        if variance <= 0.0 {
+            return Err(SimulationError::StatisticalError(
+                "Standard deviation is zero".to_string(),
+            ));
+        }
+        let std_deviation = variance.sqrt();
+        let third_central = m3 - 3.0 * mean * m2 + 2.0 * mean.powi(3);
+        let fourth_central = m4 - 4.0 * mean * m3 + 6.0 * mean.powi(2) * m2 - 3.0 * mean.powi(4);
+
+        Ok(SimulationStats {
+            min_moves,
+            max_moves: None,
+            min_moves_fraction,
+            mean,
+            variance,
+            std_deviation,
+            coeff_variation: std_deviation / mean,
+            skewness: Some(third_central / std_deviation.powi(3)),
+            kurtosis: Some(fourth_central / std_deviation.powi(4)),
             median,
         })
     }
 
+    /// Statistics of runs that always take exactly `moves` moves, with `max_moves` the
+    /// longest observed run or `None` for exact statistics
+    ///
+    /// There is no spread, so the variance is zero and the skewness and kurtosis are left
+    /// undefined.
+    pub(crate) fn certain(moves: u64, max_moves: Option<u64>) -> Self {
+        SimulationStats {
+            min_moves: moves,
+            max_moves,
+            min_moves_fraction: 1.0,
+            mean: moves as f64,
+            variance: 0.0,
+            std_deviation: 0.0,
+            coeff_variation: 0.0,
+            skewness: None,
+            kurtosis: None,
+            median: moves as f64,
+        }
+    }
+
     /// Print all statistical metrics
     pub(crate) fn print(&self) {
         println!("Shortest Path Length: {}", self.min_moves);
-        println!("Longest Observed Path Length: {}", self.max_moves);
+        if let Some(max_moves) = self.max_moves {
+            println!("Longest Observed Path Length: {}", max_moves);
+        }
         println!("Shortest Path Fraction: {}", self.min_moves_fraction);
         println!("Mean moves: {}", self.mean);
         println!("Variance: {}", self.variance);
         println!("Standard deviation: {}", self.std_deviation);
         println!("Coefficient of Variation: {}", self.coeff_variation);
-        println!("Skew: {}", self.skewness);
-        println!("Kurtosis: {}", self.kurtosis);
+        match (self.skewness, self.kurtosis) {
+            (Some(skewness), Some(kurtosis)) => {
+                println!("Skew: {}", skewness);
+                println!("Kurtosis: {}", kurtosis);
+            }
+            _ => println!("Skew and Kurtosis: undefined, as every run has the same length"),
+        }
         println!("Median: {}", self.median)
     }
 
+    /// One-line summary of the values, without labels that assume they count moves
+    pub(crate) fn summary(&self) -> String {
+        format!(
+            "mean {}, std {}, min {}{}, median {}",
+            self.mean,
+            self.std_deviation,
+            self.min_moves,
+            self.max_moves.map_or(String::new(), |max_moves| format!(", max {}", max_moves)),
+            self.median
+        )
+    }
+
     pub(crate) fn header(&self) -> String {
         format!(
             "{},{},{},{},{},{},{},{},{},{}",
@@ -102,14 +231,14 @@ impl SimulationStats {
         format!(
             "{},{},{},{},{},{},{},{},{},{}",
             self.min_moves,
-            self.max_moves,
+            self.max_moves.map_or(String::new(), |max_moves| max_moves.to_string()),
             self.min_moves_fraction,
             self.mean,
             self.variance,
             self.std_deviation,
             self.coeff_variation,
-            self.skewness,
-            self.kurtosis,
+            self.skewness.map_or(String::new(), |skewness| skewness.to_string()),
+            self.kurtosis.map_or(String::new(), |kurtosis| kurtosis.to_string()),
             self.median
         )
     }
@@ -175,9 +304,22 @@ fn calculate_median(values: &[u64]) -> f64 {
     let mut values_copy = values.to_owned();
     values_copy.sort();
     let len = values_copy.len();
-    if len % 2 == 0 {
+    if len.is_multiple_of(2) {
         (values_copy[len / 2 - 1] + values_copy[len / 2]) as f64 / 2.0
     } else {
         values_copy[len / 2] as f64
     }
 }
+
+/// Smallest number of moves `k` with P(moves <= k) >= `quantile`, for a
+/// probability mass function indexed by move count
+///
+/// Returns `None` if the cumulative probability never reaches `quantile`.
+pub fn distribution_quantile(pmf: &[f64], quantile: f64) -> Option<u64> {
+    let mut cumulative = 0.0;
+    pmf.iter().position(|&p| {
+        cumulative += p;
+        cumulative >= quantile
+    })
+    .map(|k| k as u64)
+}